@@ -35,7 +35,7 @@ fn basic_sharing_with_shares() {
   let derived_share = format!("03{derived_share}");
 
   // Shares in hex format
-  let shares = vec![
+  let shares = [
     // This share will is stored on the device
     "015a2d54d7e40eadf7d82635a2fb120ffa7a0b9bf9e07e2943f87346034e5ae1ba",
     &derived_share,
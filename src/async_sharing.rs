@@ -0,0 +1,591 @@
+//! Async versions of [share_to_writables](crate::wrapped_sharing::share_to_writables) and
+//! [reconstruct_from_srcs](crate::wrapped_sharing::reconstruct_from_srcs) over tokio's
+//! [AsyncRead] and [AsyncWrite], so shares can be streamed straight to and from sockets.
+//!
+//! The shares have the same layout as the ones from [wrapped_sharing](crate::wrapped_sharing),
+//! a header, the x-value, followed by the y-values, followed by the shared hash if verify is
+//! true. Shares created by either module can be reconstructed by the other.
+//!
+//! Unlike the sync functions, neither the secret nor the shares need to be seekable. The hash is
+//! calculated as the secret is read, and when reconstructing the bytes that may be the hash at
+//! the end of each share are held back until the end of the share is reached.
+//!
+//! Since the secret can't be seeked to find its length, the length has to be given when sharing
+//! so it can be written to the [ShareHeader].
+use crate::share_header::{
+    self, HashAlgorithm, Scheme, ShareHeader, CHECKSUM_LEN, FLAG_CHECKSUM, HEADER_LEN, MAGIC,
+};
+use crate::wrapped_sharing::{
+    check_share_headers, hash_algorithm, header_hash_algorithm, Error, SegmentReconstructor,
+    SegmentSharer, ShareOptions, READ_SEGMENT_SIZE,
+};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Shares the secret to the dests, in the same format as
+/// [share_to_writables](crate::wrapped_sharing::share_to_writables).
+///
+/// **secret_len**: The length of the secret, which is written to the header of each share. Only
+///                 this many bytes are read from the secret, and [Error::IOError] is returned if
+///                 the secret ends before then.
+///
+/// **verify**: If true, a hash is calculated from the secret and placed at the end to be used
+///             to verify reconstruction of the secret.
+pub async fn share_to_writables<R, W>(
+    secret: R,
+    secret_len: u64,
+    dests: &mut [W],
+    shares_required: u8,
+    shares_to_create: u8,
+    verify: bool,
+) -> Result<(), Error>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    share_to_writables_with_options(
+        secret,
+        secret_len,
+        dests,
+        shares_required,
+        shares_to_create,
+        &ShareOptions::from_verify(verify),
+    )
+    .await
+}
+
+/// Same as [share_to_writables], with the hash and checksum set by **options**
+pub async fn share_to_writables_with_options<R, W>(
+    secret: R,
+    secret_len: u64,
+    dests: &mut [W],
+    shares_required: u8,
+    shares_to_create: u8,
+    options: &ShareOptions,
+) -> Result<(), Error>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut rand = StdRng::from_entropy();
+    let mut header = ShareHeader::new(
+        Scheme::Gf256,
+        shares_required as u16,
+        secret_len,
+        options.hash_algorithm,
+        &mut rand,
+    );
+    let secret = secret.take(secret_len);
+    let read_len = if !options.checksum {
+        write_headers(dests, &header).await?;
+        share_to_writables_with_rng(
+            secret,
+            dests,
+            shares_required,
+            shares_to_create,
+            options.hash_algorithm,
+            &mut rand,
+        )
+        .await?
+    } else {
+        header.flags |= FLAG_CHECKSUM;
+        // Everything written to a dest is also hashed, so the checksum can be appended at the end
+        let mut hashers = vec![blake3::Hasher::new(); dests.len()];
+        let mut checksum_dests: Vec<ChecksumWriter<&mut W>> = dests
+            .iter_mut()
+            .zip(hashers.iter_mut())
+            .map(|(dest, hasher)| ChecksumWriter { dest, hasher })
+            .collect();
+        write_headers(&mut checksum_dests, &header).await?;
+        let read_len = share_to_writables_with_rng(
+            secret,
+            &mut checksum_dests,
+            shares_required,
+            shares_to_create,
+            options.hash_algorithm,
+            &mut rand,
+        )
+        .await?;
+        for (dest, hasher) in dests.iter_mut().zip(&hashers) {
+            dest.write_all(hasher.finalize().as_bytes()).await?;
+            dest.flush().await.ok();
+        }
+        read_len
+    };
+
+    if read_len != secret_len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(())
+}
+
+// Writes to dest, and hashes everything that was written
+struct ChecksumWriter<'h, W> {
+    dest: W,
+    hasher: &'h mut blake3::Hasher,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for ChecksumWriter<'_, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        let written = ready!(Pin::new(&mut self.dest).poll_write(cx, buf))?;
+        self.hasher.update(&buf[..written]);
+        Poll::Ready(Ok(written))
+    }
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut self.dest).poll_flush(cx)
+    }
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut self.dest).poll_shutdown(cx)
+    }
+}
+
+// Writes the header to each dest, with the index of the share that dest receives
+async fn write_headers<W: AsyncWrite + Unpin>(
+    dests: &mut [W],
+    header: &ShareHeader,
+) -> Result<(), Error> {
+    for (i, dest) in dests.iter_mut().enumerate() {
+        dest.write_all(&header.for_index(i as u16 + 1).to_bytes()).await?;
+    }
+    Ok(())
+}
+
+// Shares the secret without a header, returning the number of bytes read from the secret
+async fn share_to_writables_with_rng<R, W>(
+    mut secret: R,
+    dests: &mut [W],
+    shares_required: u8,
+    shares_to_create: u8,
+    hash_algorithm: HashAlgorithm,
+    rand: &mut (dyn RngCore + Send),
+) -> Result<u64, Error>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    if dests.len() < (shares_to_create as usize) {
+        // Not enough dests to share shares to
+        return Err(Error::NotEnoughWriteableDestinations(
+            dests.len(),
+            shares_to_create,
+        ));
+    }
+
+    // The sharing itself is done by the same code as the sync functions, only the reading and
+    // writing is async. The x value comes first in each share, followed by the MAC key if there
+    // is one.
+    let (mut sharer, starts) =
+        SegmentSharer::new(shares_required, shares_to_create, hash_algorithm, rand)?;
+    write_shares(dests, &starts).await?;
+    let mut read_len = 0;
+    loop {
+        let secret_segment = read_segment(&mut secret, READ_SEGMENT_SIZE).await?;
+        if secret_segment.is_empty() {
+            break;
+        }
+        read_len += secret_segment.len() as u64;
+        write_shares(dests, &sharer.next_segment(secret_segment, rand).share()?).await?;
+    }
+
+    if let Some(hash) = sharer.finish(rand) {
+        write_shares(dests, &hash.share()?).await?;
+    }
+
+    // Flush writes to all dests to ensure all bytes are written
+    for dest in dests.iter_mut() {
+        dest.flush().await.ok();
+    }
+    Ok(read_len)
+}
+
+// Writes each share's bytes to its dest
+async fn write_shares<W: AsyncWrite + Unpin>(
+    dests: &mut [W],
+    shares: &[Vec<u8>],
+) -> Result<(), Error> {
+    for (share, dest) in shares.iter().zip(dests.iter_mut()) {
+        dest.write_all(share).await?;
+    }
+    Ok(())
+}
+
+/// Reconstructs the secret from the srcs and writes it to secret. Each src is read until EOF,
+/// and they should all read the same number of bytes.
+///
+/// **verify**: If true, a hash is assumed to exist at the end of the shares and will be used
+///             to verify secret reconstruction. NOTE: This will fail if the secret was not
+///             shared with verify set to true. If the shares have headers, the hash
+///             algorithm is taken from them instead, but they must still have a hash if this
+///             is true.
+///
+/// If the shares have headers, each src must end exactly where its header says it does, or
+/// [WrongShareLength](share_header::Error::WrongShareLength) is returned.
+///
+/// If the shares have checksums, they are checked once the end of the shares is reached, so the
+/// secret will already have been written when [Error::CorruptShare] is returned.
+pub async fn reconstruct_from_srcs<R, W>(
+    mut secret: W,
+    srcs: &mut [R],
+    verify: bool,
+) -> Result<(), Error>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    // First, get the first byte from each share, which is either the x value or the start of a
+    // header
+    let mut x_vals = Vec::with_capacity(srcs.len());
+    for src in srcs.iter_mut() {
+        x_vals.push(src.read_u8().await?);
+    }
+    let num_headers = x_vals.iter().filter(|byte| **byte == MAGIC[0]).count();
+    let mut hash_algorithm = hash_algorithm(verify);
+    // Hashes everything read from each share if the shares have checksums
+    let mut hashers: Option<Vec<blake3::Hasher>> = None;
+    // The length of each share according to its header
+    let mut expected_len = None;
+    if num_headers == srcs.len() {
+        let mut headers = Vec::with_capacity(srcs.len());
+        let mut header_hashers = Vec::with_capacity(srcs.len());
+        for (src, x_val) in srcs.iter_mut().zip(x_vals.iter_mut()) {
+            let mut bytes = [0u8; HEADER_LEN + 1];
+            bytes[0] = *x_val;
+            src.read_exact(&mut bytes[1..]).await?;
+            headers.push(ShareHeader::from_bytes(&bytes)?);
+            *x_val = bytes[HEADER_LEN];
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(&bytes);
+            header_hashers.push(hasher);
+        }
+        let header = check_share_headers(&headers, &x_vals)?;
+        hash_algorithm = header_hash_algorithm(header, verify)?;
+        expected_len = Some(HEADER_LEN as u64 + header.body_len());
+        if header.has_checksum() {
+            hashers = Some(header_hashers);
+        }
+    } else if num_headers != 0 {
+        return Err(share_header::Error::MixedHeaders.into());
+    }
+
+    // The hash and checksum can't be told apart from the rest of the share until EOF, so the
+    // last bytes read from each src are held back until more bytes are read after them
+    let checksum_len = if hashers.is_some() { CHECKSUM_LEN } else { 0 };
+    let held_len = hash_algorithm.digest_len() + checksum_len;
+    let mut held: Vec<Vec<u8>> = vec![Vec::with_capacity(READ_SEGMENT_SIZE + held_len); srcs.len()];
+
+    // The MAC key, if there is one, is shared in front of the secret
+    let key_len = hash_algorithm.key_len();
+    let mut key_shares = vec![vec![0u8; key_len]; srcs.len()];
+    for (src, key_share) in srcs.iter_mut().zip(key_shares.iter_mut()) {
+        src.read_exact(key_share).await?;
+    }
+    if let Some(hashers) = hashers.as_mut() {
+        for (hasher, key_share) in hashers.iter_mut().zip(&key_shares) {
+            hasher.update(key_share);
+        }
+    }
+    let mut reconstructor = SegmentReconstructor::new(&x_vals, hash_algorithm, &key_shares)?;
+    // The number of bytes read from each src so far
+    let start_len = if expected_len.is_some() { HEADER_LEN } else { 0 };
+    let mut read_lens = vec![(start_len + 1 + key_len) as u64; srcs.len()];
+    loop {
+        let mut read_any = false;
+        for (i, src) in srcs.iter_mut().enumerate() {
+            let segment = read_segment(src, READ_SEGMENT_SIZE).await?;
+            read_any |= !segment.is_empty();
+            read_lens[i] += segment.len() as u64;
+            held[i].extend_from_slice(&segment);
+
+            // Stop as soon as a share is longer than its header says, or once it ends early
+            let at_eof = segment.len() < READ_SEGMENT_SIZE;
+            if let Some(expected) = expected_len {
+                if read_lens[i] > expected || (at_eof && read_lens[i] < expected) {
+                    return Err(share_header::Error::WrongShareLength {
+                        expected,
+                        found: read_lens[i],
+                    }
+                    .into());
+                }
+            }
+        }
+        if !read_any {
+            break;
+        }
+        let len = held[0].len();
+        if let Some(i) = held.iter().position(|share| share.len() != len) {
+            return Err(crate::basic_sharing::Error::InvalidMatrix {
+                index_of_invalid_length_row: i,
+            }
+            .into());
+        }
+        let ready = len.saturating_sub(held_len);
+        let segments: Vec<Vec<u8>> = held
+            .iter_mut()
+            .map(|share| share.drain(..ready).collect())
+            .collect();
+        if ready == 0 {
+            continue;
+        }
+        if let Some(hashers) = hashers.as_mut() {
+            for (hasher, segment) in hashers.iter_mut().zip(&segments) {
+                hasher.update(segment);
+            }
+        }
+
+        secret.write_all(&reconstructor.next_segment(&segments)?).await?;
+    }
+    secret.flush().await.ok();
+
+    if let Some(hashers) = hashers {
+        for (index, (mut hasher, share)) in hashers.into_iter().zip(held.iter_mut()).enumerate() {
+            let checksum = share.split_off(share.len().saturating_sub(CHECKSUM_LEN));
+            hasher.update(share);
+            if hasher.finalize().as_bytes() != checksum.as_slice() {
+                return Err(Error::CorruptShare { index, path: None });
+            }
+        }
+    }
+
+    reconstructor.finish(&held)
+}
+
+// Reads up to num_bytes from src, which is only fewer if EOF is reached
+async fn read_segment<R: AsyncRead + Unpin>(
+    src: &mut R,
+    num_bytes: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut segment = Vec::with_capacity(num_bytes);
+    src.take(num_bytes as u64).read_to_end(&mut segment).await?;
+    Ok(segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrapped_sharing::{reconstruct, share_to_writables_with_rng as sync_share};
+    use std::io::{Cursor, Write};
+
+    #[tokio::test]
+    async fn matches_sync() {
+        let secret: Vec<u8> = (0..20_000).map(|i| (i % 253) as u8).collect();
+        let mut shares: Vec<Vec<u8>> = vec![Vec::new(); 5];
+        let mut rand = StdRng::seed_from_u64(3);
+        share_to_writables_with_rng(
+            secret.as_slice(),
+            &mut shares,
+            3,
+            5,
+            HashAlgorithm::Sha3_512Prefix,
+            &mut rand,
+        )
+        .await
+        .unwrap();
+
+        let mut sync_shares: Vec<Vec<u8>> = vec![Vec::new(); 5];
+        {
+            let mut dests: Vec<Box<dyn Write>> = sync_shares
+                .iter_mut()
+                .map(|share| Box::new(share) as Box<dyn Write>)
+                .collect();
+            let mut rand = StdRng::seed_from_u64(3);
+            sync_share(
+                Cursor::new(&secret),
+                &mut dests,
+                3,
+                5,
+                HashAlgorithm::Sha3_512Prefix,
+                READ_SEGMENT_SIZE,
+                &mut rand,
+            )
+            .unwrap();
+        }
+        assert_eq!(sync_shares, shares);
+        assert_eq!(secret, reconstruct(&shares[..3], true).unwrap());
+
+        let mut srcs: Vec<&[u8]> = shares[2..].iter().map(|share| share.as_slice()).collect();
+        let mut recon = Vec::new();
+        reconstruct_from_srcs(&mut recon, &mut srcs, true)
+            .await
+            .unwrap();
+        assert_eq!(secret, recon);
+    }
+
+    #[tokio::test]
+    async fn with_header() {
+        let secret: Vec<u8> = (0..100).collect();
+        let shares = crate::wrapped_sharing::share(&secret, 2, 3, true).unwrap();
+        let mut srcs: Vec<&[u8]> = shares[..2].iter().map(|share| share.as_slice()).collect();
+        let mut recon = Vec::new();
+        reconstruct_from_srcs(&mut recon, &mut srcs, false)
+            .await
+            .unwrap();
+        assert_eq!(secret, recon);
+    }
+
+    #[tokio::test]
+    async fn mac() {
+        let secret: Vec<u8> = (0..100).collect();
+        let options = crate::wrapped_sharing::ShareOptions {
+            hash_algorithm: HashAlgorithm::Blake3Mac,
+            checksum: true,
+        };
+        let mut shares = crate::wrapped_sharing::share_with_options(&secret, 2, 3, &options)
+            .unwrap();
+        let mut srcs: Vec<&[u8]> = shares[..2].iter().map(|share| share.as_slice()).collect();
+        let mut recon = Vec::new();
+        reconstruct_from_srcs(&mut recon, &mut srcs, false)
+            .await
+            .unwrap();
+        assert_eq!(secret, recon);
+
+        let mut shares_async: Vec<Vec<u8>> = vec![Vec::new(); 3];
+        let mut rand = StdRng::seed_from_u64(9);
+        share_to_writables_with_rng(
+            secret.as_slice(),
+            &mut shares_async,
+            2,
+            3,
+            HashAlgorithm::Blake3Mac,
+            &mut rand,
+        )
+        .await
+        .unwrap();
+        assert_eq!(1 + 32 + 100 + 32, shares_async[0].len());
+
+        // Tamper with the secret and fix up the checksum, which only the MAC catches
+        shares[1][HEADER_LEN + 40] ^= 1;
+        let checksum_start = shares[1].len() - CHECKSUM_LEN;
+        let checksum = blake3::hash(&shares[1][..checksum_start]);
+        shares[1][checksum_start..].copy_from_slice(checksum.as_bytes());
+        let mut srcs: Vec<&[u8]> = shares[..2].iter().map(|share| share.as_slice()).collect();
+        assert!(matches!(
+            reconstruct_from_srcs(Vec::new(), &mut srcs, false).await,
+            Err(Error::VerificationFailure(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn checksum() {
+        let secret: Vec<u8> = (0..100).collect();
+        let mut shares = crate::wrapped_sharing::share_with_options(
+            &secret,
+            2,
+            3,
+            &crate::wrapped_sharing::ShareOptions::default(),
+        )
+        .unwrap();
+        let mut srcs: Vec<&[u8]> = shares[1..].iter().map(|share| share.as_slice()).collect();
+        let mut recon = Vec::new();
+        reconstruct_from_srcs(&mut recon, &mut srcs, true)
+            .await
+            .unwrap();
+        assert_eq!(secret, recon);
+
+        // Corrupt the last byte of the checksum
+        let last = shares[2].len() - 1;
+        shares[2][last] ^= 1;
+        let mut srcs: Vec<&[u8]> = shares[1..].iter().map(|share| share.as_slice()).collect();
+        assert!(matches!(
+            reconstruct_from_srcs(Vec::new(), &mut srcs, true).await,
+            Err(Error::CorruptShare { index: 1, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn wrong_length() {
+        let secret: Vec<u8> = (0..100).collect();
+        let options = crate::wrapped_sharing::ShareOptions {
+            hash_algorithm: HashAlgorithm::None,
+            checksum: false,
+        };
+        let shares = crate::wrapped_sharing::share_with_options(&secret, 2, 3, &options).unwrap();
+        let expected = shares[0].len() as u64;
+
+        let mut srcs: Vec<&[u8]> = shares[..2]
+            .iter()
+            .map(|share| &share[..share.len() - 10])
+            .collect();
+        assert!(matches!(
+            reconstruct_from_srcs(Vec::new(), &mut srcs, false).await,
+            Err(Error::HeaderError(share_header::Error::WrongShareLength { expected: e, found }))
+                if e == expected && found == expected - 10
+        ));
+
+        let mut longer: Vec<Vec<u8>> = shares[..2].to_vec();
+        for share in longer.iter_mut() {
+            share.extend_from_slice(&[0; 10]);
+        }
+        let mut srcs: Vec<&[u8]> = longer.iter().map(|share| share.as_slice()).collect();
+        assert!(matches!(
+            reconstruct_from_srcs(Vec::new(), &mut srcs, false).await,
+            Err(Error::HeaderError(share_header::Error::WrongShareLength { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn no_verify() {
+        let secret = b"a short secret".to_vec();
+        let mut shares: Vec<Vec<u8>> = vec![Vec::new(); 3];
+        let secret_len = secret.len() as u64;
+        share_to_writables(secret.as_slice(), secret_len, &mut shares, 2, 3, false)
+            .await
+            .unwrap();
+        assert_eq!(HEADER_LEN + secret.len() + 1, shares[0].len());
+
+        let mut srcs: Vec<&[u8]> = shares[1..].iter().map(|share| share.as_slice()).collect();
+        let mut recon = Vec::new();
+        reconstruct_from_srcs(&mut recon, &mut srcs, false)
+            .await
+            .unwrap();
+        assert_eq!(secret, recon);
+
+        // The header says there is no hash, so there is nothing to verify
+        let mut srcs: Vec<&[u8]> = shares[1..].iter().map(|share| share.as_slice()).collect();
+        assert!(matches!(
+            reconstruct_from_srcs(&mut Vec::new(), &mut srcs, true).await,
+            Err(Error::NoHashToVerify)
+        ));
+
+        // The secret is shorter than the length that was given
+        assert!(matches!(
+            share_to_writables(secret.as_slice(), secret_len + 1, &mut shares, 2, 3, false).await,
+            Err(Error::IOError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn with_options() {
+        let secret: Vec<u8> = (0..20_000).map(|i| (i % 13) as u8).collect();
+        let mut shares: Vec<Vec<u8>> = vec![Vec::new(); 3];
+        let options = crate::wrapped_sharing::ShareOptions::default();
+        let secret_len = secret.len() as u64;
+        share_to_writables_with_options(secret.as_slice(), secret_len, &mut shares, 2, 3, &options)
+            .await
+            .unwrap();
+        let header = ShareHeader::from_bytes(&shares[0]).unwrap();
+        assert!(header.has_checksum());
+        assert_eq!(HEADER_LEN as u64 + header.body_len(), shares[0].len() as u64);
+        assert_eq!(secret, reconstruct(&shares[1..], false).unwrap());
+
+        // Corrupting a share is caught by the checksum
+        shares[0][HEADER_LEN + 100] ^= 1;
+        let mut srcs: Vec<&[u8]> = shares[..2].iter().map(|share| share.as_slice()).collect();
+        assert!(matches!(
+            reconstruct_from_srcs(Vec::new(), &mut srcs, false).await,
+            Err(Error::CorruptShare { index: 0, .. })
+        ));
+    }
+}
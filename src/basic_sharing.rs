@@ -1,6 +1,8 @@
 use rand::rngs::StdRng;
 use rand::{Rng, RngCore, SeedableRng};
+use crate::batch::Reconstructor;
 use crate::geometry::*;
+use std::collections::HashSet;
 
 /// Creates a vector of points that serve as the list of shares for a given byte of data.
 ///
@@ -22,15 +24,7 @@ pub fn from_secret(
     coefficients: Option<Vec<u8>>,
     rand: Option<&mut dyn RngCore>,
 ) -> Result<Vec<(u8, u8)>, Error> {
-    if shares_required > shares_to_create {
-        return Err(Error::UnreconstructableSecret(
-            shares_to_create,
-            shares_required,
-        ));
-    }
-    if shares_to_create < 2 {
-        return Err(Error::InvalidNumberOfShares(shares_to_create));
-    }
+    check_share_counts(shares_required, shares_to_create)?;
 
     // Use the given rng or if none was given, use from entropy
     let mut shares: Vec<(u8, u8)> = Vec::with_capacity(shares_to_create as usize);
@@ -64,13 +58,119 @@ pub fn from_secret(
     }
 
     for i in 1..=shares_to_create {
-        let curr_x = i as u8;
+        let curr_x = i;
         let curr_y = share_poly.get_y_value(curr_x);
         shares.push((curr_x, curr_y));
     }
     Ok(shares)
 }
 
+/// Checks that **shares_to_create** shares can be created with **shares_required** of them
+/// being needed to reconstruct the secret.
+pub(crate) fn check_share_counts(shares_required: u8, shares_to_create: u8) -> Result<(), Error> {
+    if shares_required > shares_to_create {
+        return Err(Error::UnreconstructableSecret(
+            shares_to_create,
+            shares_required,
+        ));
+    }
+    if shares_to_create < 2 {
+        return Err(Error::InvalidNumberOfShares(shares_to_create));
+    }
+    Ok(())
+}
+
+/// Checks that **shares_to_create** shares can be created over the field **F** with
+/// **shares_required** of them being needed to reconstruct the secret.
+fn check_field_share_counts<F: Field>(
+    shares_required: usize,
+    shares_to_create: usize,
+) -> Result<(), Error> {
+    if shares_required < 1
+        || shares_required > shares_to_create
+        || shares_to_create < 2
+        || shares_to_create as u64 > F::MAX_SHARES
+    {
+        return Err(Error::InvalidShareCounts {
+            shares_required,
+            shares_to_create,
+        });
+    }
+    Ok(())
+}
+
+/// Creates a vector of points that serve as the list of shares for a given element of any
+/// [Field].
+///
+/// This is the generic version of [from_secret]. The x-values of the shares are
+/// [Field::from_index] of 1 through **shares_to_create**, and the coefficients are generated
+/// with [Field::random].
+///
+/// **shares_to_create** can be at most [Field::MAX_SHARES].
+///
+/// *For the rest of the arguments, see [from_secret]*
+pub fn from_field_secret<F: Field>(
+    secret: F,
+    shares_required: usize,
+    shares_to_create: usize,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<(F, F)>, Error> {
+    check_field_share_counts::<F>(shares_required, shares_to_create)?;
+
+    let mut from_entropy: Box<dyn RngCore>;
+    let rand = match rand {
+        Some(rng) => rng,
+        None => {
+            from_entropy = Box::new(StdRng::from_entropy());
+            &mut from_entropy
+        }
+    };
+
+    let mut coeffs = Vec::with_capacity(shares_required);
+    coeffs.push(secret);
+    for _ in 1..shares_required {
+        coeffs.push(F::random(rand));
+    }
+    let share_poly = GaloisPolynomial::from_coeffs(coeffs);
+
+    Ok((1..=shares_to_create as u64)
+        .map(|i| {
+            let x = F::from_index(i);
+            let y = share_poly.evaluate(&x);
+            (x, y)
+        })
+        .collect())
+}
+
+/// Reconstructs a secret that is an element of any [Field] from a given Vector of shares.
+///
+/// This is the generic version of [reconstruct_secret].
+///
+/// This will return [Error::DuplicateShare] if more than one share has the same x-value.
+pub fn reconstruct_field_secret<F: Field>(shares: Vec<(F, F)>) -> Result<F, Error> {
+    let mut x_values = HashSet::with_capacity(shares.len());
+    for (x, _) in &shares {
+        let x = x.to_bytes();
+        if x_values.contains(&x) {
+            return Err(duplicate_share(&x));
+        }
+        x_values.insert(x);
+    }
+    Ok(GaloisPolynomial::evaluate_from_points(&shares, &F::zero()))
+}
+
+/// Creates the error for a share with the x-value encoded as **x** being given more than once.
+///
+/// Shares are only ever created with x-values up to [Field::MAX_SHARES], so one that doesn't fit
+/// in a u16 wasn't created by this crate.
+fn duplicate_share(x: &[u8]) -> Error {
+    let (high, low) = x.split_at(x.len().saturating_sub(2));
+    if high.iter().any(|b| *b != 0) {
+        return Error::InvalidShareEncoding;
+    }
+    Error::DuplicateShare(low.iter().fold(0, |acc, b| (acc << 8) | u16::from(*b)))
+}
+
 /// Reconstructs a secret from a given Vector of shares (points) and returns that secret.
 ///
 /// No guarantees are made that the shares are valid together and that the secret is valid.
@@ -84,6 +184,95 @@ pub fn reconstruct_secret(shares: Vec<(u8, u8)>) -> u8 {
     GaloisPolynomial::get_y_intercept_from_points(shares.as_slice())
 }
 
+/// Reconstructs a secret from a given Vector of shares (points), correcting any incorrect shares
+/// along the way.
+///
+/// Up to (shares.len() - shares_required) / 2 shares can be incorrect, so in order to correct
+/// E shares, at least shares_required + 2E shares are needed.
+///
+/// Returns the secret along with the x-values of the shares that were incorrect.
+///
+/// **shares:** The vector of shares that are used to regenerate the polynomial and finding the
+///     secret.
+///
+/// **shares_required:** The number of shares that were required to recreate the secret when it
+///     was shared.
+///
+/// This will return an error if **shares.len() < shares_required**, or if there are too many
+/// incorrect shares to correct.
+pub fn reconstruct_secret_robust(
+    shares: Vec<(u8, u8)>,
+    shares_required: u8,
+) -> Result<(u8, Vec<u8>), Error> {
+    check_shares(&shares, shares_required)?;
+    let poly = GaloisPolynomial::from_points_with_errors(&shares, shares_required as usize)
+        .ok_or_else(|| too_many_corrupt_shares(shares.len(), shares_required))?;
+
+    let bad_x_values = shares
+        .iter()
+        .filter(|(x, y)| poly.get_y_value(*x) != *y)
+        .map(|(x, _)| *x)
+        .collect();
+    Ok((poly.get_y_value(0), bad_x_values))
+}
+
+// Checks there are enough shares to reconstruct, and none of them are repeated
+fn check_shares(shares: &[(u8, u8)], shares_required: u8) -> Result<(), Error> {
+    if shares.len() < shares_required as usize {
+        return Err(Error::NotEnoughShares {
+            given: shares.len() as u8,
+            required: shares_required,
+        });
+    }
+    for (i, (x, _)) in shares.iter().enumerate() {
+        if shares[..i].iter().any(|(prev_x, _)| prev_x == x) {
+            return Err(Error::DuplicateShare((*x).into()));
+        }
+    }
+    Ok(())
+}
+
+/// Reconstructs a secret from a given Vector of shares (points), checking that all of them lie on
+/// the same polynomial.
+///
+/// The polynomial is interpolated from the first **shares_required** shares, and every share
+/// after those is checked against it. Giving more shares than required lets incorrect shares be
+/// detected rather than silently producing an incorrect secret.
+///
+/// **shares:** The vector of shares that are used to regenerate the polynomial and finding the
+///     secret.
+///
+/// **shares_required:** The number of shares that were required to recreate the secret when it
+///     was shared.
+///
+/// This will return [Error::InconsistentShares] if the shares do not all lie on the same
+/// polynomial. The suspects are the incorrect shares if there are few enough of them to be
+/// found, otherwise they are every share given.
+pub fn reconstruct_secret_checked(shares: Vec<(u8, u8)>, shares_required: u8) -> Result<u8, Error> {
+    check_shares(&shares, shares_required)?;
+    let (base, extra) = shares.split_at(shares_required as usize);
+    let poly = GaloisPolynomial::from_points(base);
+    if extra.iter().all(|(x, y)| poly.get_y_value(*x) == *y) {
+        return Ok(poly.get_y_value(0));
+    }
+
+    let suspects = match reconstruct_secret_robust(shares.clone(), shares_required) {
+        Ok((_, bad_x_values)) => bad_x_values,
+        Err(_) => {
+            let mut x_values: Vec<u8> = shares.iter().map(|(x, _)| *x).collect();
+            x_values.sort_unstable();
+            x_values
+        }
+    };
+    Err(Error::InconsistentShares { suspects })
+}
+
+fn too_many_corrupt_shares(num_shares: usize, shares_required: u8) -> Error {
+    Error::TooManyCorruptShares {
+        correctable: ((num_shares - shares_required as usize) / 2) as u8,
+    }
+}
+
 // Make the legnth of each coefficient equal to the length of the secret
 pub fn normalize_coefficients(
   secret_len: usize,
@@ -187,6 +376,23 @@ pub fn from_secrets(
 ///
 /// *For the rest of the arguments, see [reconstruct_secret]*
 pub fn reconstruct_secrets(share_lists: Vec<Vec<(u8, u8)>>) -> Result<Vec<u8>, Error> {
+    // Each share normally has the same x-value for every byte, in which case the Lagrange
+    // weights only need to be calculated once for the whole secret
+    let x_values: Option<Vec<u8>> = share_lists
+        .iter()
+        .map(|share_list| {
+            let x = share_list.first()?.0;
+            share_list.iter().all(|(x_val, _)| *x_val == x).then_some(x)
+        })
+        .collect();
+    if let Some(reconstructor) = x_values.and_then(|x_values| Reconstructor::new(&x_values).ok()) {
+        let ys: Vec<Vec<u8>> = share_lists
+            .into_iter()
+            .map(|share_list| share_list.into_iter().map(|(_, y)| y).collect())
+            .collect();
+        return reconstructor.reconstruct(&ys);
+    }
+
     let mut secrets: Vec<u8> = Vec::with_capacity(share_lists[0].len());
     let share_lists = transpose_vec_matrix(share_lists)?;
     for point_list in share_lists {
@@ -195,6 +401,110 @@ pub fn reconstruct_secrets(share_lists: Vec<Vec<(u8, u8)>>) -> Result<Vec<u8>, E
     Ok(secrets)
 }
 
+/// This is the generic version of [from_secrets], sharing each element of the secret over the
+/// field **F**.
+///
+/// The shares are returned in the same format as [from_secrets].
+///
+/// *For the arguments, see [from_field_secret]*
+pub fn from_field_secrets<F: Field>(
+    secret: &[F],
+    shares_required: usize,
+    shares_to_create: usize,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<(F, F)>>, Error> {
+    if secret.is_empty() {
+        return Err(Error::EmptySecretArray);
+    }
+
+    let mut from_entropy: Box<dyn RngCore>;
+    let mut rand = match rand {
+        Some(rng) => rng,
+        None => {
+            from_entropy = Box::new(StdRng::from_entropy());
+            &mut from_entropy
+        }
+    };
+
+    let list_of_share_lists = secret
+        .iter()
+        .map(|s| from_field_secret(s.clone(), shares_required, shares_to_create, Some(&mut rand)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    transpose_vec_matrix(list_of_share_lists)
+}
+
+/// This is the generic version of [reconstruct_secrets], reconstructing each element of the
+/// secret over the field **F**.
+///
+/// *For the format of the share lists, see [reconstruct_secrets]*
+pub fn reconstruct_field_secrets<F: Field>(share_lists: Vec<Vec<(F, F)>>) -> Result<Vec<F>, Error> {
+    let share_lists = transpose_vec_matrix(share_lists)?;
+    share_lists
+        .into_iter()
+        .map(reconstruct_field_secret)
+        .collect()
+}
+
+/// This is a wrapper around [reconstruct_secret_robust] that iterates over each Vec of shares
+/// and reconstructs their respective byte of the secret, correcting incorrect shares.
+///
+/// Returns the secret along with the x-values of every share that was incorrect for at least
+/// one byte, in ascending order.
+///
+/// *For the format of the share lists, see [reconstruct_secrets]*
+///
+/// *For the rest of the arguments, see [reconstruct_secret_robust]*
+pub fn reconstruct_secrets_robust(
+    share_lists: Vec<Vec<(u8, u8)>>,
+    shares_required: u8,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let mut secrets: Vec<u8> = Vec::with_capacity(share_lists[0].len());
+    let mut bad_x_values: Vec<u8> = Vec::new();
+    let share_lists = transpose_vec_matrix(share_lists)?;
+    for point_list in share_lists {
+        let (secret, bad) = reconstruct_secret_robust(point_list, shares_required)?;
+        secrets.push(secret);
+        bad_x_values.extend(bad);
+    }
+    bad_x_values.sort_unstable();
+    bad_x_values.dedup();
+    Ok((secrets, bad_x_values))
+}
+
+/// This is a wrapper around [reconstruct_secret_checked] that iterates over each Vec of shares
+/// and reconstructs their respective byte of the secret, checking that the shares are
+/// consistent.
+///
+/// If any byte is inconsistent, the suspects of every inconsistent byte are returned together
+/// in [Error::InconsistentShares], in ascending order.
+///
+/// *For the format of the share lists, see [reconstruct_secrets]*
+///
+/// *For the rest of the arguments, see [reconstruct_secret_checked]*
+pub fn reconstruct_secrets_checked(
+    share_lists: Vec<Vec<(u8, u8)>>,
+    shares_required: u8,
+) -> Result<Vec<u8>, Error> {
+    let mut secrets: Vec<u8> = Vec::with_capacity(share_lists[0].len());
+    let mut all_suspects: Vec<u8> = Vec::new();
+    let share_lists = transpose_vec_matrix(share_lists)?;
+    for point_list in share_lists {
+        match reconstruct_secret_checked(point_list, shares_required) {
+            Ok(secret) => secrets.push(secret),
+            Err(Error::InconsistentShares { suspects }) => all_suspects.extend(suspects),
+            Err(e) => return Err(e),
+        }
+    }
+    if !all_suspects.is_empty() {
+        all_suspects.sort_unstable();
+        all_suspects.dedup();
+        return Err(Error::InconsistentShares {
+            suspects: all_suspects,
+        });
+    }
+    Ok(secrets)
+}
+
 /// Wrapper around its corresponding share function, this simply uses the [reduce_share]
 /// function to reduce the size of the share.
 ///
@@ -244,7 +554,175 @@ pub fn from_secrets_no_points(
 ///
 /// See [reconstruct_secrets] for more documentation.
 pub fn reconstruct_secrets_no_points(share_lists: Vec<Vec<u8>>) -> Result<Vec<u8>, Error> {
-    reconstruct_secrets(share_lists.into_iter().map(expand_share).collect())
+    let x_values: Vec<u8> = share_lists.iter().map(|share| share[0]).collect();
+    let ys: Vec<&[u8]> = share_lists.iter().map(|share| &share[1..]).collect();
+    Reconstructor::new(&x_values)?.reconstruct(&ys)
+}
+
+/// Wrapper around [reconstruct_secrets_robust], to be used with shares created using
+/// [from_secrets_no_points]
+///
+/// See [reconstruct_secrets_no_points] and [reconstruct_secrets_robust] for more documentation.
+pub fn reconstruct_secrets_no_points_robust(
+    share_lists: Vec<Vec<u8>>,
+    shares_required: u8,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    reconstruct_secrets_robust(
+        share_lists.into_iter().map(expand_share).collect(),
+        shares_required,
+    )
+}
+
+/// Wrapper around [reconstruct_secrets_checked], to be used with shares created using
+/// [from_secrets_no_points]
+///
+/// See [reconstruct_secrets_no_points] and [reconstruct_secrets_checked] for more documentation.
+pub fn reconstruct_secrets_no_points_checked(
+    share_lists: Vec<Vec<u8>>,
+    shares_required: u8,
+) -> Result<Vec<u8>, Error> {
+    reconstruct_secrets_checked(
+        share_lists.into_iter().map(expand_share).collect(),
+        shares_required,
+    )
+}
+
+/// Shares the secret over GF(65536), two bytes at a time, allowing up to 65535 shares to be
+/// created.
+///
+/// Returns the shares in the format
+/// (2-byte X-value),(1-byte padding flag),(2-byte y-value for each 2-byte word of the secret)
+///
+/// All values are big-endian. If the secret has an odd length, it is padded with a zero byte
+/// and the padding flag is set to 1, so the padding can be removed on reconstruction.
+///
+/// **rand:** The rng source for the generated coefficients in the sharing process.
+/// The default is StdRng::from_entropy()
+///
+/// *For the rest of the arguments, see [from_secret]*
+pub fn from_secrets_wide(
+    secret: &[u8],
+    shares_required: u16,
+    shares_to_create: u16,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let words: Vec<Gf65536> = secret
+        .chunks(2)
+        .map(|chunk| Gf65536(u16::from_be_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)])))
+        .collect();
+    let padding = (secret.len() % 2) as u8;
+
+    let share_lists = from_field_secrets(
+        &words,
+        shares_required as usize,
+        shares_to_create as usize,
+        rand,
+    )?;
+    Ok(share_lists
+        .into_iter()
+        .map(|share_list| {
+            let mut share = Vec::with_capacity(3 + 2 * share_list.len());
+            share.extend_from_slice(&share_list[0].0.to_bytes());
+            share.push(padding);
+            for (_, y) in share_list {
+                share.extend_from_slice(&y.to_bytes());
+            }
+            share
+        })
+        .collect())
+}
+
+/// Reconstructs a secret from shares created using [from_secrets_wide]
+///
+/// This will return [Error::InvalidShareEncoding] if the shares are not all valid shares of
+/// the same length.
+pub fn reconstruct_secrets_wide(shares: Vec<Vec<u8>>) -> Result<Vec<u8>, Error> {
+    let first = shares.first().ok_or(Error::NotEnoughShares {
+        given: 0,
+        required: 1,
+    })?;
+    let padding = *first.get(2).ok_or(Error::InvalidShareEncoding)?;
+    if padding > 1 || first.len() % 2 == 0 || (padding == 1 && first.len() < 5) {
+        return Err(Error::InvalidShareEncoding);
+    }
+
+    let mut share_lists = Vec::with_capacity(shares.len());
+    let mut x_values = HashSet::with_capacity(shares.len());
+    for share in &shares {
+        if share.len() != first.len() || share[2] != padding {
+            return Err(Error::InvalidShareEncoding);
+        }
+        let x = Gf65536(u16::from_be_bytes([share[0], share[1]]));
+        if *x == 0 {
+            return Err(Error::InvalidShareEncoding);
+        }
+        if !x_values.insert(*x) {
+            return Err(Error::DuplicateShare(*x));
+        }
+        share_lists.push(
+            share[3..]
+                .chunks(2)
+                .map(|y| (x, Gf65536(u16::from_be_bytes([y[0], y[1]]))))
+                .collect(),
+        );
+    }
+
+    let mut secret: Vec<u8> = reconstruct_field_secrets(share_lists)?
+        .into_iter()
+        .flat_map(|word| word.to_bytes())
+        .collect();
+    secret.truncate(secret.len() - padding as usize);
+    Ok(secret)
+}
+
+/// Shares a secret that is a single element of the prime field modulo **M**, such as a curve
+/// private key.
+///
+/// Unlike the bytewise sharing, the secret is shared as a whole, so the shares can be used
+/// directly in threshold protocols over the same field.
+///
+/// **secret:** The big-endian encoding of the secret, which must be exactly
+/// [PrimeModulus::ENCODED_LEN] bytes and less than the modulus.
+///
+/// Returns the shares in the format (X-value),(Y-value), each as big-endian
+/// [PrimeModulus::ENCODED_LEN] bytes.
+///
+/// NOTE: The field arithmetic is not constant-time, see [Fp].
+///
+/// *For the rest of the arguments, see [from_field_secret]*
+pub fn from_secret_scalar<M: PrimeModulus>(
+    secret: &[u8],
+    shares_required: usize,
+    shares_to_create: usize,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let secret = Fp::<M>::from_bytes(secret).ok_or(Error::InvalidScalar)?;
+    Ok(
+        from_field_secret(secret, shares_required, shares_to_create, rand)?
+            .into_iter()
+            .map(|(x, y)| [x.to_bytes(), y.to_bytes()].concat())
+            .collect(),
+    )
+}
+
+/// Reconstructs a secret from shares created using [from_secret_scalar], returning its
+/// big-endian encoding.
+///
+/// This will return [Error::InvalidShareEncoding] if any of the shares can't be decoded, and
+/// [Error::DuplicateShare] if more than one share has the same x-value.
+pub fn reconstruct_secret_scalar<M: PrimeModulus>(shares: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+    let points = shares
+        .iter()
+        .map(|share| {
+            if share.len() != 2 * M::ENCODED_LEN {
+                return None;
+            }
+            let (x, y) = share.split_at(M::ENCODED_LEN);
+            Some((Fp::<M>::from_bytes(x)?, Fp::<M>::from_bytes(y)?))
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(Error::InvalidShareEncoding)?;
+    Ok(reconstruct_field_secret(points)?.to_bytes())
 }
 
 /// This 'compresses' a share by pulling out it's X value from each point since
@@ -308,6 +786,24 @@ pub enum Error {
     InvalidNumberOfCoefficients(u8, u8),
     UnreconstructableSecret(u8, u8),
     EmptySecretArray,
+    /// More than one share with this x-value was given
+    DuplicateShare(u16),
+    /// More shares were incorrect than the number that could be corrected
+    TooManyCorruptShares { correctable: u8 },
+    /// The shares do not all lie on the same polynomial, these are the x-values of the shares
+    /// that are likely to be incorrect
+    InconsistentShares { suspects: Vec<u8> },
+    /// The share counts can't be used with the field, either fewer than 2 shares are to be
+    /// created, more are required than are created, or more are created than the field has
+    /// x-values for
+    InvalidShareCounts {
+        shares_required: usize,
+        shares_to_create: usize,
+    },
+    /// A share could not be decoded, or is not the same length as the other shares
+    InvalidShareEncoding,
+    /// The secret is not the encoding of an element of the field
+    InvalidScalar,
 }
 
 impl std::fmt::Display for Error {
@@ -337,6 +833,29 @@ impl std::fmt::Display for Error {
                 "Can't create less shares than required to reconstruct. Create: {}, Req: {}",
                 to_create, required
             ),
+            Error::DuplicateShare(x) => {
+                write!(f, "Share with x-value {} was given more than once", x)
+            }
+            Error::TooManyCorruptShares { correctable } => write!(
+                f,
+                "Too many incorrect shares, at most {} could be corrected",
+                correctable
+            ),
+            Error::InconsistentShares { suspects } => write!(
+                f,
+                "Shares are inconsistent, suspected incorrect shares: {:?}",
+                suspects
+            ),
+            Error::InvalidShareCounts {
+                shares_required,
+                shares_to_create,
+            } => write!(
+                f,
+                "Can't create {} shares with {} of them required",
+                shares_to_create, shares_required
+            ),
+            Error::InvalidShareEncoding => write!(f, "Share is not a valid encoding"),
+            Error::InvalidScalar => write!(f, "Secret is not a valid field element"),
         }
     }
 }
@@ -408,7 +927,7 @@ mod tests {
         let now = Instant::now();
 
         let share_lists =
-            from_secrets(secret.as_bytes(), shares_required, shares_to_create).unwrap();
+            from_secrets(secret.as_bytes(), shares_required, shares_to_create, None, None).unwrap();
 
         let recon_secret_vec = reconstruct_secrets(share_lists).unwrap();
         let recon_secret = String::from_utf8(recon_secret_vec).unwrap();
@@ -420,6 +939,133 @@ mod tests {
         assert_eq!(secret, &recon_secret[..])
     }
 
+    #[test]
+    fn robust() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        let secret = vec![10, 20, 30, 40, 50];
+        let mut shares = from_secrets_no_points(&secret, 3, 7, None, Some(&mut rand)).unwrap();
+        shares[1][2] ^= 0xff;
+        shares[4][5] ^= 0x01;
+
+        let (recon, bad) = reconstruct_secrets_no_points_robust(shares.clone(), 3).unwrap();
+        assert_eq!(secret, recon);
+        assert_eq!(vec![2, 5], bad);
+
+        shares[6][2] ^= 0x10;
+        shares[0][2] ^= 0x20;
+        assert!(matches!(
+            reconstruct_secrets_no_points_robust(shares, 3),
+            Err(Error::TooManyCorruptShares { correctable: 2 })
+        ));
+    }
+
+    #[test]
+    fn checked() {
+        let secret = vec![10, 20, 30, 40, 50];
+        let mut shares = from_secrets_no_points(&secret, 3, 5, None, None).unwrap();
+        assert_eq!(
+            secret,
+            reconstruct_secrets_no_points_checked(shares.clone(), 3).unwrap()
+        );
+
+        shares[0][3] ^= 0x42;
+        match reconstruct_secrets_no_points_checked(shares.clone(), 3) {
+            Err(Error::InconsistentShares { suspects }) => assert_eq!(vec![1], suspects),
+            other => panic!("Expected InconsistentShares, got {:?}", other),
+        }
+
+        // Too few extra shares to tell which share is incorrect
+        match reconstruct_secrets_no_points_checked(shares[..4].to_vec(), 3) {
+            Err(Error::InconsistentShares { suspects }) => assert_eq!(vec![1, 2, 3, 4], suspects),
+            other => panic!("Expected InconsistentShares, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn field_secrets() {
+        let secret: Vec<Coeff> = [10, 20, 30, 40, 50].into_iter().map(Coeff).collect();
+        let shares = from_field_secrets(&secret, 3, 5, None).unwrap();
+        assert_eq!(secret, reconstruct_field_secrets(shares[2..].to_vec()).unwrap());
+
+        // Shares over GF(256) are the same as the bytewise shares
+        let byte_shares: Vec<Vec<(u8, u8)>> = shares
+            .iter()
+            .map(|share| share.iter().map(|(x, y)| (**x, **y)).collect())
+            .collect();
+        assert_eq!(
+            vec![10, 20, 30, 40, 50],
+            reconstruct_secrets(byte_shares[..3].to_vec()).unwrap()
+        );
+
+        assert!(matches!(
+            from_field_secrets(&secret, 3, 256, None),
+            Err(Error::InvalidShareCounts { .. })
+        ));
+
+        let repeated = vec![shares[1].clone(), shares[3].clone(), shares[1].clone()];
+        assert!(matches!(
+            reconstruct_field_secrets(repeated),
+            Err(Error::DuplicateShare(2))
+        ));
+    }
+
+    #[test]
+    fn wide() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        for secret in [vec![10, 20, 30, 40, 50], vec![10, 20, 30, 40]] {
+            let shares = from_secrets_wide(&secret, 300, 1000, Some(&mut rand)).unwrap();
+            assert_eq!(1000, shares.len());
+            assert_eq!([0x03, 0xe8], shares[999][..2]);
+            assert_eq!(3 + secret.len().div_ceil(2) * 2, shares[0].len());
+
+            let recon = reconstruct_secrets_wide(shares[600..900].to_vec()).unwrap();
+            assert_eq!(secret, recon);
+        }
+
+        assert!(matches!(
+            reconstruct_secrets_wide(vec![vec![0, 1, 2, 0, 0]]),
+            Err(Error::InvalidShareEncoding)
+        ));
+
+        let mut shares = from_secrets_wide(&[1, 2, 3], 2, 300, None).unwrap();
+        shares[1] = shares[299].clone();
+        shares[2] = shares[299].clone();
+        assert!(matches!(
+            reconstruct_secrets_wide(shares[..3].to_vec()),
+            Err(Error::DuplicateShare(300))
+        ));
+    }
+
+    #[test]
+    fn scalar() {
+        let mut secret = [0xab; 32];
+        secret[0] = 0x12;
+        let shares = from_secret_scalar::<Secp256k1Order>(&secret, 3, 5, None).unwrap();
+        assert_eq!(64, shares[0].len());
+        assert_eq!(
+            secret.to_vec(),
+            reconstruct_secret_scalar::<Secp256k1Order>(&shares[1..4]).unwrap()
+        );
+
+        let shares = from_secret_scalar::<P25519>(&secret, 2, 3, None).unwrap();
+        assert_eq!(
+            secret.to_vec(),
+            reconstruct_secret_scalar::<P25519>(&shares[1..]).unwrap()
+        );
+
+        // The secret is larger than the modulus
+        assert!(matches!(
+            from_secret_scalar::<P25519>(&[0xff; 32], 2, 3, None),
+            Err(Error::InvalidScalar)
+        ));
+
+        let repeated = vec![shares[0].clone(), shares[2].clone(), shares[2].clone()];
+        assert!(matches!(
+            reconstruct_secret_scalar::<P25519>(&repeated),
+            Err(Error::DuplicateShare(3))
+        ));
+    }
+
     #[test]
     fn no_points() {
         let secret = vec![10, 20, 30, 40, 50];
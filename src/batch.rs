@@ -0,0 +1,242 @@
+//! Batch sharing and reconstruction engine for large secrets.
+//!
+//! Rather than building a polynomial per byte like
+//! [from_secrets](crate::basic_sharing::from_secrets), the coefficients for a whole segment are
+//! generated at once and each share is evaluated for every byte of the segment with Horner's
+//! method, 8 bytes at a time packed into a u64. The y-values are appended directly to each
+//! share's output buffer.
+//!
+//! Multiplying by an x-value only branches on the bits of the x-value, which is public, so this
+//! is constant-time with regards to the secret and coefficients.
+//!
+//! [Reconstructor] does the same for reconstruction, calculating the Lagrange weights for a set
+//! of x-values once and then reconstructing every byte as a dot product of the weights and the
+//! y-values.
+//!
+//! The shares are compatible with every other GF(256) share, so they can be reconstructed with
+//! [reconstruct_secrets_no_points](crate::basic_sharing::reconstruct_secrets_no_points) once
+//! the x-value is placed in front.
+use crate::basic_sharing::{check_share_counts, Error};
+use crate::geometry::GaloisPolynomial;
+use rand::RngCore;
+
+const LOW_BITS: u64 = 0x7f7f_7f7f_7f7f_7f7f;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+// The primitive polynomial x^8 + x^4 + x^3 + x^2 + 1 without its x^8 term
+const REDUCTION: u64 = 0x1d;
+
+/// Shares each byte of **secret**, appending the y-values of the share with x-value i + 1 to
+/// **outputs**\[i\].
+///
+/// The number of shares created is **outputs**.len(), which can be at most 255.
+///
+/// **secret:** The segment of the secret to share
+///
+/// **shares_required:** The number of shares required to recreate the secret
+///
+/// **outputs:** One buffer per share, which the share's y-values are appended to
+///
+/// **rand:** The rng source for the generated coefficients. Exactly
+/// (**shares_required** - 1) * **secret**.len() bytes are drawn from it.
+pub fn share_segment(
+    secret: &[u8],
+    shares_required: u8,
+    outputs: &mut [Vec<u8>],
+    rand: &mut dyn RngCore,
+) -> Result<(), Error> {
+    if secret.is_empty() {
+        return Err(Error::EmptySecretArray);
+    }
+    let shares_to_create = u8::try_from(outputs.len())
+        .map_err(|_| Error::InvalidNumberOfShares(u8::MAX))?;
+    check_share_counts(shares_required, shares_to_create)?;
+
+    // One row per coefficient, with the secret as the x^0 coefficient
+    let mut rows: Vec<Vec<u64>> = Vec::with_capacity(shares_required as usize);
+    rows.push(to_words(secret));
+    let mut random = vec![0u8; secret.len()];
+    for _ in 1..shares_required {
+        rand.fill_bytes(&mut random);
+        rows.push(to_words(&random));
+    }
+
+    let (highest, rest) = rows.split_last().unwrap();
+    let mut acc = highest.clone();
+    for (i, output) in outputs.iter_mut().enumerate() {
+        let x = (i + 1) as u8;
+        acc.copy_from_slice(highest);
+        for row in rest.iter().rev() {
+            for (acc_word, co_word) in acc.iter_mut().zip(row) {
+                *acc_word = mul_word(*acc_word, x) ^ co_word;
+            }
+        }
+
+        let start = output.len();
+        output.reserve(acc.len() * 8);
+        for word in &acc {
+            output.extend_from_slice(&word.to_le_bytes());
+        }
+        output.truncate(start + secret.len());
+    }
+    Ok(())
+}
+
+/// Reconstructs secrets from shares with a fixed set of x-values, using Lagrange weights that
+/// are only calculated once.
+#[derive(Clone, Debug)]
+pub struct Reconstructor {
+    x_values: Vec<u8>,
+    // The value of each share's Lagrange basis polynomial at 0
+    weights: Vec<u8>,
+}
+
+impl Reconstructor {
+    /// Precomputes the Lagrange weights for shares with the given x-values.
+    ///
+    /// This will return an error if there are no x-values, or if any of them are repeated.
+    pub fn new(x_values: &[u8]) -> Result<Self, Error> {
+        if x_values.is_empty() {
+            return Err(Error::NotEnoughShares {
+                given: 0,
+                required: 1,
+            });
+        }
+        for (i, x) in x_values.iter().enumerate() {
+            if x_values[..i].contains(x) {
+                return Err(Error::DuplicateShare((*x).into()));
+            }
+        }
+        Ok(Self {
+            x_values: x_values.to_vec(),
+            weights: GaloisPolynomial::lagrange_weights(x_values, 0)
+                .into_iter()
+                .map(|weight| *weight)
+                .collect(),
+        })
+    }
+
+    /// The x-values the weights were calculated for
+    pub fn x_values(&self) -> &[u8] {
+        &self.x_values
+    }
+
+    /// Reconstructs each byte of the secret from the y-values of the shares.
+    ///
+    /// **ys:** The y-values of each share, in the same order as the x-values given to
+    /// [Reconstructor::new]. They must all be the same length.
+    pub fn reconstruct<T: AsRef<[u8]>>(&self, ys: &[T]) -> Result<Vec<u8>, Error> {
+        if ys.len() != self.weights.len() {
+            return Err(Error::NotEnoughShares {
+                given: ys.len() as u8,
+                required: self.weights.len() as u8,
+            });
+        }
+        let len = ys[0].as_ref().len();
+        if let Some(i) = ys.iter().position(|share| share.as_ref().len() != len) {
+            return Err(Error::InvalidMatrix {
+                index_of_invalid_length_row: i,
+            });
+        }
+
+        let mut acc = vec![0u64; len.div_ceil(8)];
+        for (weight, share) in self.weights.iter().zip(ys) {
+            for (acc_word, chunk) in acc.iter_mut().zip(share.as_ref().chunks(8)) {
+                *acc_word ^= mul_word(to_word(chunk), *weight);
+            }
+        }
+
+        let mut secret = Vec::with_capacity(acc.len() * 8);
+        for word in &acc {
+            secret.extend_from_slice(&word.to_le_bytes());
+        }
+        secret.truncate(len);
+        Ok(secret)
+    }
+}
+
+// Packs the bytes into words, with the last word padded with zeros
+fn to_words(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks(8)
+        .map(to_word)
+        .collect()
+}
+
+// Packs up to 8 bytes into a word, padded with zeros
+fn to_word(chunk: &[u8]) -> u64 {
+    let mut word = [0u8; 8];
+    word[..chunk.len()].copy_from_slice(chunk);
+    u64::from_le_bytes(word)
+}
+
+// Multiplies each of the 8 bytes in the word by x
+fn xtime(word: u64) -> u64 {
+    let carries = (word & HIGH_BITS) >> 7;
+    ((word & LOW_BITS) << 1) ^ (carries * REDUCTION)
+}
+
+// Multiplies each of the 8 bytes in the word by x_val
+fn mul_word(mut word: u64, mut x_val: u8) -> u64 {
+    let mut prod = 0;
+    while x_val != 0 {
+        if x_val & 1 == 1 {
+            prod ^= word;
+        }
+        word = xtime(word);
+        x_val >>= 1;
+    }
+    prod
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_sharing::reconstruct_secrets_no_points;
+    use crate::geometry::Coeff;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn mul_word_matches_coeff() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for x in 0..=255u8 {
+            for chunk in bytes.chunks(8) {
+                let prod = mul_word(to_words(chunk)[0], x).to_le_bytes();
+                for (byte, prod_byte) in chunk.iter().zip(prod) {
+                    assert_eq!(*(Coeff(*byte) * Coeff(x)), prod_byte);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn share_and_reconstruct() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        let secret: Vec<u8> = (0..=100).collect();
+        let mut outputs: Vec<Vec<u8>> = (1..=5).map(|x| vec![x]).collect();
+        share_segment(&secret[..60], 3, &mut outputs, &mut rand).unwrap();
+        share_segment(&secret[60..], 3, &mut outputs, &mut rand).unwrap();
+
+        assert_eq!(secret.len() + 1, outputs[0].len());
+        assert_eq!(
+            secret,
+            reconstruct_secrets_no_points(outputs[2..].to_vec()).unwrap()
+        );
+    }
+
+    #[test]
+    fn reconstructor() {
+        let secret: Vec<u8> = (0..=100).collect();
+        let mut outputs: Vec<Vec<u8>> = vec![Vec::new(); 6];
+        share_segment(&secret, 4, &mut outputs, &mut StdRng::seed_from_u64(1)).unwrap();
+
+        let recon = Reconstructor::new(&[6, 2, 5, 1]).unwrap();
+        let ys = [&outputs[5], &outputs[1], &outputs[4], &outputs[0]];
+        assert_eq!(secret, recon.reconstruct(&ys).unwrap());
+
+        assert!(matches!(
+            Reconstructor::new(&[1, 2, 1]),
+            Err(Error::DuplicateShare(1))
+        ));
+    }
+}
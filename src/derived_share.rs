@@ -1,37 +1,307 @@
-use rand::RngCore;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use argon2::{Algorithm, Argon2, Params, Version};
 use blake3::hash;
-use eyre::Result;
-use crate::{geometry::Coeff, basic_sharing::from_secrets_no_points};
+use unicode_normalization::UnicodeNormalization;
+use eyre::{bail, eyre, Result};
+use crate::{
+  geometry::{solve_linear_system, Coeff},
+  basic_sharing::{
+    from_secrets_no_points, reconstruct_secrets_no_points, reconstruct_secrets_no_points_robust,
+  },
+};
+
+/// The x-value [with_derived_share] pins the derived share at
+pub const DERIVED_SHARE_X: u8 = 3;
+
+/// Calculates the coefficients a1..a(t-1) of each byte's polynomial, so that the polynomial passes
+/// through every derived share.
+///
+/// With k derived shares, a(k+1)..a(t-1) are random and a1..ak are found by solving, for each
+/// derived share (x, y), `a1*x + ... + ak*x^k = y - a0 - a(k+1)*x^(k+1) - ... - a(t-1)*x^(t-1)`
+fn calculate_derived_coeffs(
+  secret: &[u8],
+  derived_shares: &[(u8, Vec<u8>)],
+  shares_required: u8,
+  rand: &mut dyn RngCore,
+) -> Result<Vec<Vec<u8>>> {
+  let num_solved = derived_shares.len();
+  let num_coeffs = shares_required as usize - 1;
+
+  let mut coeffs = vec![vec![0u8; secret.len()]; num_coeffs];
+  for coeff in coeffs[num_solved..].iter_mut() {
+    rand.fill_bytes(coeff);
+  }
+
+  // powers[j][i] is x_j^(i + 1), which is the same for every byte
+  let powers: Vec<Vec<Coeff>> = derived_shares
+    .iter()
+    .map(|(x, _)| {
+      let mut power = Coeff(1);
+      (0..num_coeffs).map(|_| { power = power * Coeff(*x); power }).collect()
+    })
+    .collect();
+  let matrix: Vec<Vec<Coeff>> = powers.iter().map(|row| row[..num_solved].to_vec()).collect();
 
-/// This will calculate `a1 = (f3 - a0) / 3`
-fn calculate_derived_coeff(secret: &[u8], derived_share: &[u8]) -> Vec<u8> {
-  let mut coeff = vec![];
-  
   // Note! operations must take place in the Galois finite field
   for (i, s) in secret.iter().enumerate() {
-    let f3 = Coeff(derived_share[i]);
-    let a0 = Coeff(*s);
-    let a1 = (f3 - a0) / Coeff(3);
-
-    coeff.push(a1.0);
+    let rhs = derived_shares
+      .iter()
+      .zip(&powers)
+      .map(|((_, share), row)| {
+        let fixed = (num_solved..num_coeffs)
+          .fold(Coeff(*s), |acc, k| acc + Coeff(coeffs[k][i]) * row[k]);
+        Coeff(share[i]) - fixed
+      })
+      .collect();
+    let solution = solve_linear_system(matrix.clone(), rhs)
+      .ok_or_else(|| eyre!("the derived shares' x-values must be distinct and non-zero"))?;
+    for (coeff, co) in coeffs.iter_mut().zip(solution) {
+      coeff[i] = co.0;
+    }
   }
 
-  coeff
+  Ok(coeffs)
 }
 
 pub fn derived_share_hex(seeds: Vec<&str>) -> String {
   hex::encode(derived_share(seeds))
 }
 
-pub fn derived_share(mut seeds: Vec<&str>) -> Vec<u8> {
+/// Derives a share from the seeds with an unsalted BLAKE3 hash, which is [Kdf::Blake3Legacy].
+///
+/// This is fast to brute-force, so new shares should be derived with [Kdf::argon2id] and
+/// [derived_share_from_answers]. The seeds are also encoded ambiguously, e.g. `["ab", "c"]` and
+/// `["a", "bc"]` derive the same share, which [canonical_seed] fixes.
+pub fn derived_share(seeds: Vec<&str>) -> Vec<u8> {
+  hash(seed_input(seeds).as_bytes()).as_bytes().to_vec()
+} 
+
+// The seeds lowercased and concatenated in sorted order
+fn seed_input(mut seeds: Vec<&str>) -> String {
   // make sure the seeds are sorted
   seeds.sort();
 
-  let seed = seeds.into_iter()
-  .fold("".to_string(), |acc, elem| format!("{}{}", acc, elem.to_lowercase()));
+  seeds.into_iter()
+  .fold("".to_string(), |acc, elem| format!("{}{}", acc, elem.to_lowercase()))
+}
 
-  hash(seed.as_bytes()).as_bytes().to_vec()
-} 
+/// The length of the key each [Kdf] derives from the seeds. Derived shares up to this long are a
+/// prefix of the key, and longer ones are expanded from it
+pub const DERIVED_SHARE_LEN: usize = 32;
+
+// The BLAKE3 key derivation context for expanding the key to the length of the derived share
+const EXPAND_CONTEXT: &str = "sss-rs 2026-10-18 derived share expansion";
+
+/// The length of the random salt created by [Kdf::argon2id]
+pub const SALT_LEN: usize = 16;
+
+/// The longest Argon2id salt, since [Kdf::to_bytes] encodes its length in a byte
+pub const MAX_SALT_LEN: usize = u8::MAX as usize;
+
+const KDF_ID_BLAKE3_LEGACY: u8 = 0;
+const KDF_ID_ARGON2ID: u8 = 1;
+
+/// The key derivation function a share is derived from its seeds with, along with its parameters.
+///
+/// This isn't secret, and should be stored alongside the device share with [Kdf::to_bytes] so
+/// the same share can be derived again when restoring the secret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kdf {
+  /// The unsalted BLAKE3 hash used by [derived_share], kept so that shares derived by older
+  /// versions can still be derived again
+  Blake3Legacy,
+  /// Argon2id (v0x13) with a per-user random salt
+  Argon2id {
+    /// Memory cost in KiB
+    m_cost: u32,
+    /// Number of iterations
+    t_cost: u32,
+    /// Degree of parallelism
+    p_cost: u32,
+    salt: Vec<u8>,
+  },
+}
+
+impl Kdf {
+  /// Argon2id with a new random salt and the recommended cost parameters of 19 MiB of memory,
+  /// 2 iterations and 1 degree of parallelism
+  pub fn argon2id(rand: &mut dyn RngCore) -> Self {
+    Self::argon2id_with_params(19 * 1024, 2, 1, rand)
+  }
+
+  /// Argon2id with a new random salt and the given cost parameters
+  pub fn argon2id_with_params(
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    rand: &mut dyn RngCore,
+  ) -> Self {
+    let mut salt = vec![0u8; SALT_LEN];
+    rand.fill_bytes(&mut salt);
+    Kdf::Argon2id { m_cost, t_cost, p_cost, salt }
+  }
+
+  /// Argon2id with the given cost parameters and salt, which must be between
+  /// [argon2::MIN_SALT_LEN] and [MAX_SALT_LEN] bytes
+  pub fn argon2id_with_salt(m_cost: u32, t_cost: u32, p_cost: u32, salt: Vec<u8>) -> Result<Self> {
+    let kdf = Kdf::Argon2id { m_cost, t_cost, p_cost, salt };
+    kdf.check_salt()?;
+    Ok(kdf)
+  }
+
+  fn check_salt(&self) -> Result<()> {
+    if let Kdf::Argon2id { salt, .. } = self {
+      if !(argon2::MIN_SALT_LEN..=MAX_SALT_LEN).contains(&salt.len()) {
+        bail!(
+          "the Argon2id salt must be between {} and {} bytes, but is {}",
+          argon2::MIN_SALT_LEN,
+          MAX_SALT_LEN,
+          salt.len(),
+        );
+      }
+    }
+    Ok(())
+  }
+
+  /// Encodes the KDF as a 1-byte id followed by its parameters, which for Argon2id are the
+  /// memory, iteration and parallelism costs as big-endian u32s, then the 1-byte salt length
+  /// and the salt.
+  ///
+  /// Returns an error if the salt is too short or too long to be encoded.
+  pub fn to_bytes(&self) -> Result<Vec<u8>> {
+    self.check_salt()?;
+    match self {
+      Kdf::Blake3Legacy => Ok(vec![KDF_ID_BLAKE3_LEGACY]),
+      Kdf::Argon2id { m_cost, t_cost, p_cost, salt } => {
+        let mut bytes = Vec::with_capacity(14 + salt.len());
+        bytes.push(KDF_ID_ARGON2ID);
+        bytes.extend_from_slice(&m_cost.to_be_bytes());
+        bytes.extend_from_slice(&t_cost.to_be_bytes());
+        bytes.extend_from_slice(&p_cost.to_be_bytes());
+        bytes.push(salt.len() as u8);
+        bytes.extend_from_slice(salt);
+        Ok(bytes)
+      }
+    }
+  }
+
+  /// Decodes a KDF encoded with [Kdf::to_bytes]
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+    match bytes.split_first() {
+      Some((&KDF_ID_BLAKE3_LEGACY, [])) => Ok(Kdf::Blake3Legacy),
+      Some((&KDF_ID_ARGON2ID, params)) if params.len() >= 13 => {
+        let read_u32 = |i: usize| u32::from_be_bytes(params[i..i + 4].try_into().unwrap());
+        let salt = &params[13..];
+        if salt.len() != params[12] as usize {
+          bail!("the Argon2id salt is {} bytes, but should be {}", salt.len(), params[12]);
+        }
+        Kdf::argon2id_with_salt(read_u32(0), read_u32(4), read_u32(8), salt.to_vec())
+      }
+      Some((&KDF_ID_ARGON2ID, _)) => bail!("the Argon2id parameters are truncated"),
+      Some((id, _)) => bail!("unknown KDF id {}", id),
+      None => bail!("the KDF is empty"),
+    }
+  }
+}
+
+/// Derives a share of **len** bytes, which should be the length of the secret, from the seeds
+/// with the given KDF.
+///
+/// The seeds are encoded the same way as [derived_share], which is ambiguous, see
+/// [derived_share_from_answers] for the canonical encoding.
+pub fn derived_share_with_kdf(seeds: Vec<&str>, kdf: &Kdf, len: usize) -> Result<Vec<u8>> {
+  derive(seed_input(seeds).as_bytes(), kdf, len)
+}
+
+/// Derives a share of **len** bytes, which should be the length of the secret, from answers to
+/// security questions, given as (question id, answer), with the given KDF. The answers are
+/// encoded with [canonical_seed], so they can be given in any order.
+pub fn derived_share_from_answers(
+  answers: &[(&str, &str)],
+  kdf: &Kdf,
+  len: usize,
+) -> Result<Vec<u8>> {
+  derive(&canonical_seed(answers)?, kdf, len)
+}
+
+// Derives a DERIVED_SHARE_LEN byte key with the KDF. If len is at most DERIVED_SHARE_LEN, the
+// first len bytes of the key are returned, which is the share older versions pinned for secrets
+// of that length. Otherwise, it's expanded to len bytes with BLAKE3's extendable output, with len
+// as part of the input so that longer shares of different lengths aren't prefixes of each other.
+fn derive(input: &[u8], kdf: &Kdf, len: usize) -> Result<Vec<u8>> {
+  if len == 0 {
+    bail!("a derived share can't be empty");
+  }
+  let mut key = derive_key(input, kdf)?;
+  if len <= DERIVED_SHARE_LEN {
+    key.truncate(len);
+    return Ok(key);
+  }
+
+  let mut share = vec![0u8; len];
+  blake3::Hasher::new_derive_key(EXPAND_CONTEXT)
+    .update(&(len as u64).to_be_bytes())
+    .update(&key)
+    .finalize_xof()
+    .fill(&mut share);
+  Ok(share)
+}
+
+fn derive_key(input: &[u8], kdf: &Kdf) -> Result<Vec<u8>> {
+  match kdf {
+    Kdf::Blake3Legacy => Ok(hash(input).as_bytes().to_vec()),
+    Kdf::Argon2id { m_cost, t_cost, p_cost, salt } => {
+      let params = Params::new(*m_cost, *t_cost, *p_cost, Some(DERIVED_SHARE_LEN))
+        .map_err(|e| eyre!("invalid Argon2id parameters: {}", e))?;
+      let mut share = vec![0u8; DERIVED_SHARE_LEN];
+      Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(input, salt, &mut share)
+        .map_err(|e| eyre!("Argon2id failed: {}", e))?;
+      Ok(share)
+    }
+  }
+}
+
+// Prefixed to the canonical encoding, so it can never be mistaken for a legacy seed
+const CANONICAL_SEED_TAG: &[u8] = b"sss-rs derived share seed v1";
+
+/// Normalizes a question id or answer, with Unicode NFKC normalization, lowercasing, and
+/// trimming whitespace and collapsing each run of it into a single space
+pub fn normalize_seed(seed: &str) -> String {
+  let lowercase = seed.nfkc().collect::<String>().to_lowercase();
+  lowercase.nfkc().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Encodes answers to security questions, given as (question id, answer), so that different
+/// answers never encode the same.
+///
+/// Each question id and answer is normalized with [normalize_seed], and the answers are sorted
+/// by question id. The encoding is a fixed tag, the number of answers as a big-endian u32, then
+/// each question id followed by its answer, each prefixed with its length as a big-endian u32.
+/// Since each answer is bound to its question, answers can't be swapped between questions.
+///
+/// This returns an error if no answers are given or a question id is repeated.
+pub fn canonical_seed(answers: &[(&str, &str)]) -> Result<Vec<u8>> {
+  if answers.is_empty() {
+    bail!("no answers were given");
+  }
+  let mut fields: Vec<(String, String)> = answers
+    .iter()
+    .map(|(question_id, answer)| (normalize_seed(question_id), normalize_seed(answer)))
+    .collect();
+  fields.sort();
+  if let Some(pair) = fields.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+    bail!("the question id {:?} is repeated", pair[0].0);
+  }
+
+  let mut encoded = CANONICAL_SEED_TAG.to_vec();
+  encoded.extend_from_slice(&(fields.len() as u32).to_be_bytes());
+  for field in fields.iter().flat_map(|(question_id, answer)| [question_id, answer]) {
+    encoded.extend_from_slice(&(field.len() as u32).to_be_bytes());
+    encoded.extend_from_slice(field.as_bytes());
+  }
+  Ok(encoded)
+}
 
 /// Creates SSS shares.
 /// The way it works is fairly simple. In a 2 of N scheme we SSS constructs a polynomial of degree 2 - 1.
@@ -46,6 +316,8 @@ pub fn derived_share(mut seeds: Vec<&str>) -> Vec<u8> {
 /// Now we can restore the secret by using 2 shares. One share can be stored on a device of in an encrypted form,
 /// on some decentralized storage. The second share can be a hash of answers to questions users know. With 2 shares
 /// user can restore the secret.
+///
+/// This works for any number of shares required, see [with_derived_shares].
 pub fn with_derived_share(
   secret: &[u8],
   seeds: Vec<&str>,
@@ -55,17 +327,680 @@ pub fn with_derived_share(
 ) -> Result<Vec<String>> {
   
   // The third share (f(3)) which is derived form the hash of the given seeds
-  let derived_share = derived_share(seeds);
-  let coeff = calculate_derived_coeff(secret, &derived_share);
+  let derived_share = derived_share_with_kdf(seeds, &Kdf::Blake3Legacy, secret.len())?;
 
-  let shares = from_secrets_no_points(
+  with_derived_shares(
     secret,
+    &[(DERIVED_SHARE_X, derived_share)],
     shares_required,
     shares_to_create,
-    Some(vec![coeff]),
     rand,
+  )
+}
+
+/// Same as [with_derived_share], but the share is derived with **kdf**, see
+/// [derived_share_with_kdf]. The KDF must be stored alongside the device share.
+pub fn with_kdf_derived_share(
+  secret: &[u8],
+  seeds: Vec<&str>,
+  kdf: &Kdf,
+  shares_required: u8,
+  shares_to_create: u8,
+  rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<String>> {
+  let derived_share = derived_share_with_kdf(seeds, kdf, secret.len())?;
+
+  with_derived_shares(
+    secret,
+    &[(DERIVED_SHARE_X, derived_share)],
+    shares_required,
+    shares_to_create,
+    rand,
+  )
+}
+
+/// Same as [with_kdf_derived_share], but the share is derived from answers to security
+/// questions, given as (question id, answer), see [derived_share_from_answers].
+pub fn with_answers_derived_share(
+  secret: &[u8],
+  answers: &[(&str, &str)],
+  kdf: &Kdf,
+  shares_required: u8,
+  shares_to_create: u8,
+  rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<String>> {
+  let derived_share = derived_share_from_answers(answers, kdf, secret.len())?;
+
+  with_derived_shares(
+    secret,
+    &[(DERIVED_SHARE_X, derived_share)],
+    shares_required,
+    shares_to_create,
+    rand,
+  )
+}
+
+/// Creates SSS shares where the polynomial is pinned to pass through each of **derived_shares**,
+/// given as (x-value, y-values), so each of them can be used as a share.
+///
+/// Up to `shares_required - 1` shares can be derived, each at its own non-zero x-value, and each
+/// must be the same length as the secret. The remaining coefficients are random, and the pinned
+/// ones are found by solving a linear system over GF(256). E.g. in a 3 of 5 scheme, one share
+/// can be derived from security answers and another from a PIN, so the secret can be restored
+/// with both of them and any one of the created shares.
+///
+/// If a derived share's x-value is also one of the created shares' (1..=**shares_to_create**),
+/// that share is the same as the derived share.
+pub fn with_derived_shares(
+  secret: &[u8],
+  derived_shares: &[(u8, Vec<u8>)],
+  shares_required: u8,
+  shares_to_create: u8,
+  rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<String>> {
+  if shares_required < 2 || derived_shares.len() >= shares_required as usize {
+    bail!(
+      "{} derived shares given, but at most {} can be derived when {} shares are required",
+      derived_shares.len(),
+      shares_required.saturating_sub(1),
+      shares_required,
+    );
+  }
+  for (i, (x, share)) in derived_shares.iter().enumerate() {
+    if *x == 0 || derived_shares[..i].iter().any(|(other_x, _)| other_x == x) {
+      bail!("the derived shares' x-values must be distinct and non-zero, found {}", x);
+    }
+    if share.len() != secret.len() {
+      bail!(
+        "the derived share at x = {} is {} bytes, but the secret is {} bytes",
+        x,
+        share.len(),
+        secret.len(),
+      );
+    }
+  }
+
+  let mut from_entropy;
+  let rand: &mut dyn RngCore = match rand {
+    Some(rand) => rand,
+    None => {
+      from_entropy = StdRng::from_entropy();
+      &mut from_entropy
+    }
+  };
+  let coeffs = calculate_derived_coeffs(secret, derived_shares, shares_required, rand)?;
+
+  let shares = from_secrets_no_points(
+    secret,
+    shares_required,
+    shares_to_create,
+    Some(coeffs),
+    Some(rand),
   )?;
-  let hex_shares = shares.iter().map(|s| hex::encode(&s)).collect::<Vec<_>>();
+  let hex_shares = shares.iter().map(hex::encode).collect::<Vec<_>>();
 
   Ok(hex_shares)
 }
+
+// The BLAKE3 key derivation context for the verifier of an AnswerHelper
+const VERIFIER_CONTEXT: &str = "sss-rs 2026-10-18 answer helper verifier";
+
+const ANSWER_HELPER_VERSION: u8 = 1;
+
+/// The most questions an [AnswerHelper] can be created for, which bounds the number of
+/// combinations of answers [AnswerHelper::recover] may have to try
+pub const MAX_QUESTIONS: usize = 16;
+
+/// Helper data that lets any **threshold** of the answers to m security questions derive the
+/// same share, so that a user can forget some of their answers.
+///
+/// The derived share is random, and is shared with an inner **threshold** of m sharing, with one
+/// sub-share per question. Each sub-share f_i is stored as `h_i = f_i XOR KDF(a_i)`, where
+/// KDF(a_i) is derived from the question id and answer with [derived_share_from_answers], so
+/// the helper data reveals nothing about the sub-shares without the answers. The derived share is
+/// recovered from the answers, correcting wrong ones, and checked against a BLAKE3 verifier of
+/// the derived share.
+///
+/// It isn't secret, and should be stored alongside the device share with
+/// [AnswerHelper::to_bytes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnswerHelper {
+  /// The number of correct answers needed to recover the derived share
+  pub threshold: u8,
+  /// The KDF each answer is derived with
+  pub kdf: Kdf,
+  /// The normalized question ids, in the order of **helpers**
+  pub question_ids: Vec<String>,
+  /// `h_i = f_i XOR KDF(a_i)`, each as long as the derived share
+  pub helpers: Vec<Vec<u8>>,
+  /// The BLAKE3 key derived from the derived share, which recovered candidates are checked
+  /// against
+  pub verifier: [u8; 32],
+}
+
+impl AnswerHelper {
+  /// Creates a random derived share of **len** bytes, which should be the length of the
+  /// secret, along with the helper data to recover it from any **threshold** of the answers,
+  /// which are given as (question id, answer).
+  ///
+  /// Returns the helper data and the derived share.
+  pub fn new(
+    answers: &[(&str, &str)],
+    threshold: u8,
+    kdf: Kdf,
+    len: usize,
+    rand: &mut dyn RngCore,
+  ) -> Result<(Self, Vec<u8>)> {
+    if answers.len() > MAX_QUESTIONS {
+      bail!("at most {} questions can be used, but {} were given", MAX_QUESTIONS, answers.len());
+    }
+    if threshold == 0 || threshold as usize > answers.len() {
+      bail!("the threshold must be between 1 and {}, but is {}", answers.len(), threshold);
+    }
+    let question_ids: Vec<String> =
+      answers.iter().map(|(question_id, _)| normalize_seed(question_id)).collect();
+    for (i, question_id) in question_ids.iter().enumerate() {
+      if question_ids[..i].contains(question_id) {
+        bail!("the question id {:?} is repeated", question_id);
+      }
+    }
+    if len == 0 {
+      bail!("a derived share can't be empty");
+    }
+    check_lengths(&kdf, &question_ids, len)?;
+
+    let mut derived_share = vec![0u8; len];
+    rand.fill_bytes(&mut derived_share);
+    let sub_shares = from_secrets_no_points(
+      &derived_share,
+      threshold,
+      answers.len() as u8,
+      None,
+      Some(rand),
+    )?;
+    let helpers = answers
+      .iter()
+      .zip(&sub_shares)
+      .map(|(answer, sub_share)| Ok(xor(&sub_share[1..], &answer_pad(answer, &kdf, len)?)))
+      .collect::<Result<Vec<_>>>()?;
+
+    let helper = Self {
+      threshold,
+      kdf,
+      question_ids,
+      helpers,
+      verifier: blake3::derive_key(VERIFIER_CONTEXT, &derived_share),
+    };
+    Ok((helper, derived_share))
+  }
+
+  /// Recovers the derived share from the answers, given as (question id, answer). Questions
+  /// that weren't answered can be left out, and the answers can be in any order.
+  ///
+  /// The answers are first decoded with Berlekamp-Welch, which corrects up to
+  /// `(answered - threshold) / 2` wrong answers. If more of them are wrong, every combination of
+  /// **threshold** answers is tried, even once the derived share has been found. Only the derived
+  /// share is returned, so which answers were correct isn't revealed. Returns an error if the
+  /// answers don't recover it.
+  pub fn recover(&self, answers: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let len = self.helpers.first().map_or(0, |helper| helper.len());
+    // The sub-shares, with their x-values, from the answered questions
+    let mut sub_shares = Vec::with_capacity(answers.len());
+    for (i, (question_id, helper)) in self.question_ids.iter().zip(&self.helpers).enumerate() {
+      let answer = answers.iter().find(|(id, _)| normalize_seed(id) == *question_id);
+      if let Some((_, answer)) = answer {
+        let pad = answer_pad(&(question_id.as_str(), *answer), &self.kdf, len)?;
+        sub_shares.push([vec![(i + 1) as u8], xor(helper, &pad)].concat());
+      }
+    }
+    if sub_shares.len() < self.threshold as usize {
+      bail!(
+        "{} questions were answered, but {} correct answers are required",
+        sub_shares.len(),
+        self.threshold,
+      );
+    }
+
+    // Hash equality is constant-time
+    let verifier = blake3::Hash::from(self.verifier);
+    let is_derived_share = |candidate: &[u8]| {
+      blake3::Hash::from(blake3::derive_key(VERIFIER_CONTEXT, candidate)) == verifier
+    };
+    let decoded = reconstruct_secrets_no_points_robust(sub_shares.clone(), self.threshold);
+    if let Ok((candidate, _)) = decoded {
+      if is_derived_share(&candidate) {
+        return Ok(candidate);
+      }
+    }
+
+    let mut recovered = None;
+    for combination in combinations(sub_shares.len(), self.threshold as usize) {
+      let candidate_shares = combination.iter().map(|i| sub_shares[*i].clone()).collect();
+      let candidate = reconstruct_secrets_no_points(candidate_shares)?;
+      if is_derived_share(&candidate) && recovered.is_none() {
+        recovered = Some(candidate);
+      }
+    }
+    recovered.ok_or_else(|| eyre!("not enough of the answers are correct"))
+  }
+
+  /// Encodes the helper data as a version byte, the threshold, the length of the encoded KDF
+  /// and the KDF, the length of the derived share as a big-endian u32, and the number of
+  /// questions, followed by each question id prefixed with its length as a big-endian u16 and
+  /// its helper value, and finally the verifier.
+  ///
+  /// Returns an error if any of the fields are too long for their length prefix, or if there
+  /// isn't one helper value of the same length for each question.
+  pub fn to_bytes(&self) -> Result<Vec<u8>> {
+    let len = self.helpers.first().map_or(0, |helper| helper.len());
+    if self.helpers.len() != self.question_ids.len()
+      || self.helpers.iter().any(|helper| helper.len() != len)
+    {
+      bail!("there must be one helper value of the same length for each question");
+    }
+    check_lengths(&self.kdf, &self.question_ids, len)?;
+    let kdf = self.kdf.to_bytes()?;
+    let mut bytes = vec![ANSWER_HELPER_VERSION, self.threshold, kdf.len() as u8];
+    bytes.extend_from_slice(&kdf);
+    bytes.extend_from_slice(&(len as u32).to_be_bytes());
+    bytes.push(self.question_ids.len() as u8);
+    for (question_id, helper) in self.question_ids.iter().zip(&self.helpers) {
+      bytes.extend_from_slice(&(question_id.len() as u16).to_be_bytes());
+      bytes.extend_from_slice(question_id.as_bytes());
+      bytes.extend_from_slice(helper);
+    }
+    bytes.extend_from_slice(&self.verifier);
+    Ok(bytes)
+  }
+
+  /// Decodes helper data encoded with [AnswerHelper::to_bytes]
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+    let mut rest = bytes;
+    let mut take = |num_bytes: usize| -> Result<&[u8]> {
+      if rest.len() < num_bytes {
+        bail!("the answer helper data is truncated");
+      }
+      let (taken, remaining) = rest.split_at(num_bytes);
+      rest = remaining;
+      Ok(taken)
+    };
+
+    let version = take(1)?[0];
+    if version != ANSWER_HELPER_VERSION {
+      bail!("unsupported answer helper version {}", version);
+    }
+    let threshold = take(1)?[0];
+    let kdf_len = take(1)?[0] as usize;
+    let kdf = Kdf::from_bytes(take(kdf_len)?)?;
+    let len = u32::from_be_bytes(take(4)?.try_into().unwrap()) as usize;
+    let num_questions = take(1)?[0] as usize;
+    if num_questions > MAX_QUESTIONS {
+      bail!("at most {} questions can be used, but there are {}", MAX_QUESTIONS, num_questions);
+    }
+    if threshold == 0 || threshold as usize > num_questions {
+      bail!("the threshold must be between 1 and {}, but is {}", num_questions, threshold);
+    }
+    let mut question_ids = Vec::with_capacity(num_questions);
+    let mut helpers = Vec::with_capacity(num_questions);
+    for _ in 0..num_questions {
+      let id_len = u16::from_be_bytes(take(2)?.try_into().unwrap()) as usize;
+      let question_id = String::from_utf8(take(id_len)?.to_vec())
+        .map_err(|_| eyre!("a question id isn't valid UTF-8"))?;
+      question_ids.push(question_id);
+      helpers.push(take(len)?.to_vec());
+    }
+    let verifier = take(32)?.try_into().unwrap();
+    if !rest.is_empty() {
+      bail!("the answer helper data has {} extra bytes", rest.len());
+    }
+
+    Ok(Self { threshold, kdf, question_ids, helpers, verifier })
+  }
+}
+
+// Checks that the KDF, question ids and derived share length fit in the length prefixes of
+// AnswerHelper::to_bytes
+fn check_lengths(kdf: &Kdf, question_ids: &[String], len: usize) -> Result<()> {
+  let kdf_len = kdf.to_bytes()?.len();
+  if kdf_len > u8::MAX as usize {
+    bail!("the KDF is {} bytes when encoded, but can be at most {}", kdf_len, u8::MAX);
+  }
+  if question_ids.len() > MAX_QUESTIONS {
+    bail!("at most {} questions can be used, but {} were given", MAX_QUESTIONS, question_ids.len());
+  }
+  if let Some(question_id) = question_ids.iter().find(|id| id.len() > u16::MAX as usize) {
+    bail!("a question id is {} bytes, but can be at most {}", question_id.len(), u16::MAX);
+  }
+  if len > u32::MAX as usize {
+    bail!("the derived share is {} bytes, but can be at most {}", len, u32::MAX);
+  }
+  Ok(())
+}
+
+// The pad a sub-share is XORed with, derived from a single answer and its question id
+fn answer_pad(answer: &(&str, &str), kdf: &Kdf, len: usize) -> Result<Vec<u8>> {
+  derived_share_from_answers(std::slice::from_ref(answer), kdf, len)
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+  a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+// Every combination of k of the indexes 0..n, in lexicographic order
+fn combinations(n: usize, k: usize) -> Combinations {
+  Combinations { n, next: if k <= n { Some((0..k).collect()) } else { None } }
+}
+
+// Generates the combinations one at a time, since there can be too many to hold at once
+struct Combinations {
+  n: usize,
+  next: Option<Vec<usize>>,
+}
+
+impl Iterator for Combinations {
+  type Item = Vec<usize>;
+
+  fn next(&mut self) -> Option<Vec<usize>> {
+    let combination = self.next.take()?;
+    let (n, k) = (self.n, combination.len());
+    // Find the rightmost index that can still be incremented
+    if let Some(i) = (0..k).rev().find(|i| combination[*i] < n - k + i) {
+      let mut next = combination.clone();
+      next[i] += 1;
+      for j in i + 1..k {
+        next[j] = next[j - 1] + 1;
+      }
+      self.next = Some(next);
+    }
+    Some(combination)
+  }
+}
+
+/// Same as [with_answers_derived_share], but any **answer_threshold** of the answers can derive
+/// the share, see [AnswerHelper]. The helper data must be stored alongside the device share.
+///
+/// Returns the shares and the helper data.
+pub fn with_answer_helper_derived_share(
+  secret: &[u8],
+  answers: &[(&str, &str)],
+  answer_threshold: u8,
+  kdf: Kdf,
+  shares_required: u8,
+  shares_to_create: u8,
+  rand: Option<&mut dyn RngCore>,
+) -> Result<(Vec<String>, AnswerHelper)> {
+  let mut from_entropy;
+  let rand: &mut dyn RngCore = match rand {
+    Some(rand) => rand,
+    None => {
+      from_entropy = StdRng::from_entropy();
+      &mut from_entropy
+    }
+  };
+  let (helper, derived_share) =
+    AnswerHelper::new(answers, answer_threshold, kdf, secret.len(), rand)?;
+
+  let shares = with_derived_shares(
+    secret,
+    &[(DERIVED_SHARE_X, derived_share)],
+    shares_required,
+    shares_to_create,
+    Some(rand),
+  )?;
+  Ok((shares, helper))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::basic_sharing::reconstruct_secrets_no_points;
+
+  fn with_x(x: u8, share: &[u8]) -> Vec<u8> {
+    [&[x], share].concat()
+  }
+
+  #[test]
+  fn single_derived_share() {
+    let secret = hash(b"secret").as_bytes().to_vec();
+    let seeds = vec!["crab", "Arnold"];
+    for shares_required in [2, 3] {
+      let shares = with_derived_share(&secret, seeds.clone(), shares_required, 3, None).unwrap();
+      let shares: Vec<Vec<u8>> = shares.iter().map(|s| hex::decode(s).unwrap()).collect();
+      let mut restore = shares[..shares_required as usize - 1].to_vec();
+      restore.push(with_x(DERIVED_SHARE_X, &derived_share(seeds.clone())));
+      assert_eq!(secret, reconstruct_secrets_no_points(restore).unwrap());
+    }
+  }
+
+  #[test]
+  fn kdf() {
+    let mut rand = StdRng::seed_from_u64(1);
+    let secret = hash(b"secret").as_bytes().to_vec();
+    let seeds = vec!["crab", "Arnold"];
+    let kdf = Kdf::argon2id_with_params(64, 1, 1, &mut rand);
+    assert_eq!(kdf, Kdf::from_bytes(&kdf.to_bytes().unwrap()).unwrap());
+    let legacy = Kdf::Blake3Legacy;
+    assert_eq!(legacy, Kdf::from_bytes(&legacy.to_bytes().unwrap()).unwrap());
+    let default_kdf = Kdf::argon2id(&mut rand);
+    assert_eq!(default_kdf, Kdf::from_bytes(&default_kdf.to_bytes().unwrap()).unwrap());
+    assert!(Kdf::from_bytes(&kdf.to_bytes().unwrap()[..10]).is_err());
+    assert!(Kdf::from_bytes(&[2]).is_err());
+
+    // Salts whose length doesn't fit in a byte, or that Argon2id rejects, can't be encoded
+    let long_salt = Kdf::Argon2id { m_cost: 64, t_cost: 1, p_cost: 1, salt: vec![1; 256] };
+    assert!(long_salt.to_bytes().is_err());
+    assert!(Kdf::argon2id_with_salt(64, 1, 1, vec![1; 256]).is_err());
+    assert!(Kdf::argon2id_with_salt(64, 1, 1, vec![1; 7]).is_err());
+    let salted = Kdf::argon2id_with_salt(64, 1, 1, vec![1; MAX_SALT_LEN]).unwrap();
+    assert_eq!(salted, Kdf::from_bytes(&salted.to_bytes().unwrap()).unwrap());
+
+    let shares = with_kdf_derived_share(&secret, seeds.clone(), &kdf, 2, 3, None).unwrap();
+    let device_share = hex::decode(&shares[0]).unwrap();
+    let kdf = Kdf::from_bytes(&kdf.to_bytes().unwrap()).unwrap();
+    let share = derived_share_with_kdf(vec!["Arnold", "crab"], &kdf, 32).unwrap();
+    assert_ne!(derived_share(seeds.clone()), share);
+    let restore = vec![device_share, with_x(DERIVED_SHARE_X, &share)];
+    assert_eq!(secret, reconstruct_secrets_no_points(restore).unwrap());
+
+    // A different salt derives a different share
+    let other_kdf = Kdf::argon2id_with_params(64, 1, 1, &mut rand);
+    assert_ne!(share, derived_share_with_kdf(seeds.clone(), &other_kdf, 32).unwrap());
+    let legacy_share = derived_share_with_kdf(seeds.clone(), &Kdf::Blake3Legacy, 32).unwrap();
+    assert_eq!(derived_share(seeds), legacy_share);
+  }
+
+  #[test]
+  fn legacy_short_secret() {
+    let seeds = vec!["crab", "Arnold"];
+    // The share older versions pinned for a 16-byte secret, the start of the BLAKE3 hash
+    let baseline = hex::decode("cbfe21dc6397ac095c4f805626e24040").unwrap();
+    assert_eq!(baseline, hex::decode(derived_share_hex(seeds.clone())).unwrap()[..16]);
+    let share = derived_share_with_kdf(seeds.clone(), &Kdf::Blake3Legacy, 16).unwrap();
+    assert_eq!(baseline, share);
+
+    let secret: Vec<u8> = (0..16).collect();
+    let shares = with_derived_share(&secret, seeds, 2, 3, None).unwrap();
+    let restore = vec![hex::decode(&shares[0]).unwrap(), with_x(DERIVED_SHARE_X, &baseline)];
+    assert_eq!(secret, reconstruct_secrets_no_points(restore).unwrap());
+  }
+
+  #[test]
+  fn canonical_seeds() {
+    assert_eq!(derived_share(vec!["ab", "c"]), derived_share(vec!["a", "bc"]));
+    assert_ne!(
+      canonical_seed(&[("q1", "ab"), ("q2", "c")]).unwrap(),
+      canonical_seed(&[("q1", "a"), ("q2", "bc")]).unwrap()
+    );
+    // Answers are bound to their questions, but can be given in any order
+    assert_ne!(
+      canonical_seed(&[("pet", "crab"), ("animal", "arnold")]).unwrap(),
+      canonical_seed(&[("pet", "arnold"), ("animal", "crab")]).unwrap()
+    );
+    assert_eq!(
+      canonical_seed(&[("pet", "Arnold"), ("animal", "crab")]).unwrap(),
+      canonical_seed(&[("animal", "crab"), ("pet", "Arnold")]).unwrap()
+    );
+    // A combining accent, the precomposed form, case and whitespace all normalize the same
+    assert_eq!(
+      canonical_seed(&[("cafe", " Cafe\u{301}  au\tlait ")]).unwrap(),
+      canonical_seed(&[("cafe", "caf\u{e9} au lait")]).unwrap()
+    );
+    assert!(canonical_seed(&[]).is_err());
+    assert!(canonical_seed(&[("pet", "crab"), ("Pet", "arnold")]).is_err());
+
+    let secret = hash(b"secret").as_bytes().to_vec();
+    let answers = [("pet", "Arnold"), ("animal", "crab")];
+    let shares =
+      with_answers_derived_share(&secret, &answers, &Kdf::Blake3Legacy, 2, 3, None).unwrap();
+    let share = derived_share_from_answers(&answers, &Kdf::Blake3Legacy, 32).unwrap();
+    let restore = vec![hex::decode(&shares[0]).unwrap(), with_x(DERIVED_SHARE_X, &share)];
+    assert_eq!(secret, reconstruct_secrets_no_points(restore).unwrap());
+  }
+
+  #[test]
+  fn any_length() {
+    let seeds = vec!["crab", "Arnold"];
+    let mut rand = StdRng::seed_from_u64(2);
+    let kdf = Kdf::argon2id_with_params(64, 1, 1, &mut rand);
+    for secret_len in [1, 31, 33, 100] {
+      let secret: Vec<u8> = (0..secret_len as u8).collect();
+      let shares = with_kdf_derived_share(&secret, seeds.clone(), &kdf, 2, 3, None).unwrap();
+      let share = derived_share_with_kdf(seeds.clone(), &kdf, secret_len).unwrap();
+      assert_eq!(secret_len, share.len());
+      let restore = vec![hex::decode(&shares[1]).unwrap(), with_x(DERIVED_SHARE_X, &share)];
+      assert_eq!(secret, reconstruct_secrets_no_points(restore).unwrap());
+
+      let shares = with_derived_share(&secret, seeds.clone(), 2, 3, None).unwrap();
+      let share = derived_share_with_kdf(seeds.clone(), &Kdf::Blake3Legacy, secret_len).unwrap();
+      let restore = vec![hex::decode(&shares[0]).unwrap(), with_x(DERIVED_SHARE_X, &share)];
+      assert_eq!(secret, reconstruct_secrets_no_points(restore).unwrap());
+    }
+
+    // Shares of different lengths aren't prefixes of each other
+    let short = derived_share_with_kdf(seeds.clone(), &Kdf::Blake3Legacy, 33).unwrap();
+    let long = derived_share_with_kdf(seeds.clone(), &Kdf::Blake3Legacy, 64).unwrap();
+    assert_ne!(short, long[..33]);
+
+    assert!(with_derived_share(&[], seeds.clone(), 2, 3, None).is_err());
+    assert!(derived_share_with_kdf(seeds, &Kdf::Blake3Legacy, 0).is_err());
+  }
+
+  #[test]
+  fn answer_helper() {
+    let mut rand = StdRng::seed_from_u64(3);
+    let secret: Vec<u8> = (0..40).collect();
+    let answers = [
+      ("pet", "Arnold"),
+      ("animal", "crab"),
+      ("street", "Main St"),
+      ("city", "Paris"),
+      ("teacher", "Smith"),
+    ];
+    let kdf = Kdf::argon2id_with_params(64, 1, 1, &mut rand);
+    let (shares, helper) =
+      with_answer_helper_derived_share(&secret, &answers, 3, kdf, 2, 3, Some(&mut rand)).unwrap();
+    let helper = AnswerHelper::from_bytes(&helper.to_bytes().unwrap()).unwrap();
+    let device_share = hex::decode(&shares[0]).unwrap();
+
+    let restore = |answers: &[(&str, &str)]| -> Result<Vec<u8>> {
+      let derived = helper.recover(answers)?;
+      Ok(reconstruct_secrets_no_points(vec![
+        device_share.clone(),
+        with_x(DERIVED_SHARE_X, &derived),
+      ])?)
+    };
+    assert_eq!(secret, restore(&answers).unwrap());
+    // Two answers forgotten, in any order, with different case and whitespace
+    let remembered = [("CITY", " paris"), ("pet", "arnold"), ("street", "main  st")];
+    assert_eq!(secret, restore(&remembered).unwrap());
+    // Two wrong answers
+    let some_wrong = [
+      ("pet", "Arnold"),
+      ("animal", "lobster"),
+      ("street", "Main St"),
+      ("city", "Rome"),
+      ("teacher", "Smith"),
+    ];
+    assert_eq!(secret, restore(&some_wrong).unwrap());
+
+    assert!(helper.recover(&[("pet", "Arnold"), ("animal", "crab")]).is_err());
+    assert!(helper.recover(&[("pet", "Arnold"), ("animal", "crab"), ("city", "Rome")]).is_err());
+
+    let bytes = helper.to_bytes().unwrap();
+    assert!(AnswerHelper::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(AnswerHelper::new(&answers, 6, Kdf::Blake3Legacy, 32, &mut rand).is_err());
+    assert!(AnswerHelper::new(&[("a", "1"), ("A", "2")], 1, Kdf::Blake3Legacy, 32, &mut rand)
+      .is_err());
+
+    // Fields too long for their length prefixes are rejected instead of truncated
+    let long_kdf = Kdf::argon2id_with_salt(64, 1, 1, vec![1; 250]).unwrap();
+    assert!(AnswerHelper::new(&answers, 3, long_kdf.clone(), 32, &mut rand).is_err());
+    let long_id = "q".repeat(u16::MAX as usize + 1);
+    let long_answers = [(long_id.as_str(), "1"), ("a", "2")];
+    assert!(AnswerHelper::new(&long_answers, 1, Kdf::Blake3Legacy, 32, &mut rand).is_err());
+    let mut bad_helper = helper.clone();
+    bad_helper.kdf = long_kdf;
+    assert!(bad_helper.to_bytes().is_err());
+    let mut bad_helper = helper.clone();
+    bad_helper.helpers[1].push(0);
+    assert!(bad_helper.to_bytes().is_err());
+  }
+
+  #[test]
+  fn answer_helper_many_questions() {
+    let mut rand = StdRng::seed_from_u64(4);
+    let ids: Vec<String> = (0..MAX_QUESTIONS).map(|i| format!("q{}", i)).collect();
+    let mut answers: Vec<(&str, &str)> = ids.iter().map(|id| (id.as_str(), "right")).collect();
+    let (helper, derived_share) =
+      AnswerHelper::new(&answers, 8, Kdf::Blake3Legacy, 32, &mut rand).unwrap();
+    assert_eq!(derived_share, helper.recover(&answers).unwrap());
+
+    // Berlekamp-Welch corrects up to (16 - 8) / 2 wrong answers, and the rest are found by
+    // trying every combination
+    for answer in answers[..4].iter_mut() {
+      answer.1 = "wrong";
+    }
+    assert_eq!(derived_share, helper.recover(&answers).unwrap());
+    answers[4].1 = "wrong";
+    assert_eq!(derived_share, helper.recover(&answers).unwrap());
+    for answer in answers[5..9].iter_mut() {
+      answer.1 = "wrong";
+    }
+    assert!(helper.recover(&answers).is_err());
+
+    let too_many: Vec<String> = (0..=MAX_QUESTIONS).map(|i| format!("q{}", i)).collect();
+    let too_many: Vec<(&str, &str)> = too_many.iter().map(|id| (id.as_str(), "a")).collect();
+    assert!(AnswerHelper::new(&too_many, 2, Kdf::Blake3Legacy, 32, &mut rand).is_err());
+    let mut bytes = helper.to_bytes().unwrap();
+    // The number of questions comes after the version, threshold, KDF and share length
+    bytes[2 + 1 + 1 + 4] = MAX_QUESTIONS as u8 + 1;
+    assert!(AnswerHelper::from_bytes(&bytes).is_err());
+  }
+
+  #[test]
+  fn all_combinations() {
+    assert_eq!(vec![vec![0, 1], vec![0, 2], vec![1, 2]], combinations(3, 2).collect::<Vec<_>>());
+    assert_eq!(10, combinations(5, 3).count());
+    assert_eq!(vec![Vec::<usize>::new()], combinations(3, 0).collect::<Vec<_>>());
+    assert_eq!(0, combinations(2, 3).count());
+  }
+
+  #[test]
+  fn multiple_derived_shares() {
+    let secret = hash(b"secret").as_bytes().to_vec();
+    let answers = derived_share(vec!["crab", "Arnold"]);
+    let pin = derived_share(vec!["1234"]);
+    let derived = [(200, answers.clone()), (201, pin.clone())];
+    let shares = with_derived_shares(&secret, &derived, 3, 5, None).unwrap();
+    let shares: Vec<Vec<u8>> = shares.iter().map(|s| hex::decode(s).unwrap()).collect();
+
+    let restore = vec![shares[3].clone(), with_x(200, &answers), with_x(201, &pin)];
+    assert_eq!(secret, reconstruct_secrets_no_points(restore).unwrap());
+    assert_eq!(secret, reconstruct_secrets_no_points(shares[..3].to_vec()).unwrap());
+
+    assert!(with_derived_shares(&secret, &derived, 2, 5, None).is_err());
+    assert!(with_derived_shares(&secret, &[(0, pin.clone())], 3, 5, None).is_err());
+    assert!(with_derived_shares(&secret, &[(9, pin.clone()), (9, pin)], 3, 5, None).is_err());
+    assert!(with_derived_shares(&secret, &[(9, answers[..5].to_vec())], 3, 5, None).is_err());
+  }
+}
@@ -0,0 +1,11 @@
+//! Finite fields that secrets can be shared over.
+//!
+//! [Coeff] is GF(256), the field used by all of the bytewise sharing in
+//! [basic_sharing](crate::basic_sharing) and [wrapped_sharing](crate::wrapped_sharing).
+//! [Gf65536] is GF(65536), which allows up to 65535 shares and is used by the `_wide` sharing
+//! functions. [Fp] is a prime field, with [P25519] and [Secp256k1Order] for sharing curve
+//! private keys as single elements with the `_scalar` sharing functions. Any
+//! type that implements [Field] can be shared with the generic functions in
+//! [basic_sharing](crate::basic_sharing), such as
+//! [from_field_secrets](crate::basic_sharing::from_field_secrets).
+pub use crate::geometry::{Coeff, Field, Fp, Gf65536, PrimeModulus, Secp256k1Order, P25519};
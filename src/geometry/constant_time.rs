@@ -0,0 +1,64 @@
+//! Constant-time GF(256) arithmetic, using the same field as [Coeff](super::Coeff).
+//!
+//! The default arithmetic looks up log/exp tables indexed by the operands, so which cache lines
+//! are accessed depends on the secret. These functions instead run a fixed number of iterations
+//! with no branches or lookups that depend on the operands.
+
+// The primitive polynomial x^8 + x^4 + x^3 + x^2 + 1 without its x^8 term
+const REDUCTION: u8 = 0x1d;
+
+/// Multiplies **a** and **b** using a carry-less multiply that always runs 8 iterations
+pub fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut prod = 0u8;
+    for _ in 0..8 {
+        // All ones if the lowest bit of b is set, all zeros otherwise
+        prod ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (REDUCTION & carry);
+        b >>= 1;
+    }
+    prod
+}
+
+/// Calculates the inverse of **a** as a^254, since a^255 = 1 for any non-zero a. The inverse
+/// of 0 is 0.
+pub fn inv(a: u8) -> u8 {
+    // Square-and-multiply over the bits of the public exponent 254 = 0b11111110
+    let mut result = 1u8;
+    for bit in (0..8).rev() {
+        result = mul(result, result);
+        if (254 >> bit) & 1 == 1 {
+            result = mul(result, a);
+        }
+    }
+    result
+}
+
+/// Divides **a** by **b**
+///
+/// Panics if **b** is 0, like the default arithmetic does. Whether **b** is 0 is the only
+/// information that timing reveals.
+pub fn div(a: u8, b: u8) -> u8 {
+    assert!(b != 0, "division by zero");
+    mul(a, inv(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galois_2p8::{field, Field, PrimitivePolynomialField};
+
+    #[test]
+    fn matches_table_arithmetic() {
+        let field = PrimitivePolynomialField::new(field::PRIMITIVES[0]).unwrap();
+        for a in 0..=255u8 {
+            for b in 0..=255u8 {
+                assert_eq!(field.mult(a, b), mul(a, b));
+                if b != 0 {
+                    assert_eq!(field.div(a, b), div(a, b));
+                }
+            }
+        }
+        assert_eq!(0, inv(0));
+    }
+}
@@ -0,0 +1,105 @@
+use super::Coeff;
+use rand::RngCore;
+use std::fmt::Debug;
+use std::ops::{Add, Mul, Sub};
+
+/// The arithmetic needed to share and reconstruct secrets over a finite field.
+///
+/// Elements are only required to be Clone rather than Copy, so that fields backed by big
+/// integers can implement this as well.
+pub trait Field:
+    Clone + Debug + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    /// The number of bytes in an element encoded with [Field::to_bytes]
+    const ENCODED_LEN: usize;
+
+    /// The number of distinct non-zero x-values, and therefore the most shares that can be
+    /// created
+    const MAX_SHARES: u64;
+
+    /// The additive identity
+    fn zero() -> Self;
+
+    /// The multiplicative identity
+    fn one() -> Self;
+
+    /// Returns the multiplicative inverse, or None if this is zero
+    fn inv(&self) -> Option<Self>;
+
+    /// Generates a uniformly random element
+    fn random(rng: &mut dyn RngCore) -> Self;
+
+    /// Returns the x-value of the share numbered **index**, where shares are numbered from 1 to
+    /// [Field::MAX_SHARES]
+    fn from_index(index: u64) -> Self;
+
+    /// Encodes the element as exactly [Field::ENCODED_LEN] bytes
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Decodes an element encoded with [Field::to_bytes]
+    ///
+    /// Returns None if **bytes** is not a valid encoding.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+
+    /// Returns true if this is the additive identity
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+impl Field for Coeff {
+    const ENCODED_LEN: usize = 1;
+    const MAX_SHARES: u64 = 255;
+
+    fn zero() -> Self {
+        Coeff(0)
+    }
+
+    fn one() -> Self {
+        Coeff(1)
+    }
+
+    fn inv(&self) -> Option<Self> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(Coeff(1) / *self)
+        }
+    }
+
+    fn random(rng: &mut dyn RngCore) -> Self {
+        let mut byte = [0u8];
+        rng.fill_bytes(&mut byte);
+        Coeff(byte[0])
+    }
+
+    fn from_index(index: u64) -> Self {
+        Coeff(index as u8)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.0]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [byte] => Some(Coeff(*byte)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coeff_inverse() {
+        for val in 1..=255u8 {
+            let co = Coeff(val);
+            assert_eq!(Coeff::one(), co * co.inv().unwrap());
+            assert_eq!(Some(co), Coeff::from_bytes(&co.to_bytes()));
+        }
+        assert_eq!(None, Coeff::zero().inv());
+    }
+}
@@ -316,7 +316,7 @@ impl Pow<i32> for Fraction {
     type Output = Fraction;
 
     fn pow(self, rhs: i32) -> Fraction {
-        let abs_rhs: u32 = rhs.abs() as u32;
+        let abs_rhs: u32 = rhs.unsigned_abs();
         let mut lhs = if rhs < 0 { self.flip() } else { self };
         lhs.numerator = lhs.numerator.pow(abs_rhs);
         lhs.denominator = lhs.denominator.pow(abs_rhs);
@@ -421,6 +421,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::op_ref)]
     fn i64_ops() {
         let frac = Fraction::new(21, 1);
         let frac2 = Fraction::new(7, 1);
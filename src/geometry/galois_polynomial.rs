@@ -1,4 +1,7 @@
-use galois_2p8::*;
+#[cfg(feature = "constant_time")]
+use super::constant_time;
+use super::{solve_linear_system, Field};
+use galois_2p8::{field, Field as _, PrimitivePolynomialField};
 use lazy_static::*;
 use std::ops::{Add, Deref, Div, Mul, Sub};
 
@@ -9,7 +12,7 @@ lazy_static! {
 }
 
 /// A wrapper around u8, used to implement arithmetic operations over a finite field
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Coeff(pub u8);
 
 impl Deref for Coeff {
@@ -38,150 +41,251 @@ impl Sub for Coeff {
         Coeff(FIELD.sub(*self, *rhs))
     }
 }
+// Multiplication and division use lookup tables indexed by the operands unless the
+// constant_time feature is enabled
 impl Mul for Coeff {
     type Output = Coeff;
+    #[cfg(not(feature = "constant_time"))]
     fn mul(self, rhs: Coeff) -> Coeff {
         Coeff(FIELD.mult(*self, *rhs))
     }
+    #[cfg(feature = "constant_time")]
+    fn mul(self, rhs: Coeff) -> Coeff {
+        Coeff(constant_time::mul(*self, *rhs))
+    }
 }
 impl Div for Coeff {
     type Output = Coeff;
+    #[cfg(not(feature = "constant_time"))]
     fn div(self, rhs: Coeff) -> Coeff {
         Coeff(FIELD.div(*self, *rhs))
     }
+    #[cfg(feature = "constant_time")]
+    fn div(self, rhs: Coeff) -> Coeff {
+        Coeff(constant_time::div(*self, *rhs))
+    }
 }
 
+/// A polynomial with coefficients in a finite field, GF(256) by default
 #[derive(Clone, Debug)]
-pub struct GaloisPolynomial {
-    coeffs: Vec<Coeff>,
+pub struct GaloisPolynomial<F: Field = Coeff> {
+    coeffs: Vec<F>,
 }
 
-impl GaloisPolynomial {
+impl<F: Field> GaloisPolynomial<F> {
     /// Constructs a polynomail with no coefficients
-    pub fn new() -> GaloisPolynomial {
+    pub fn new() -> Self {
         Self {
             coeffs: Vec::with_capacity(8),
         }
     }
 
-    /// Copies the values from the coeffs slice into a vec as the coefficients of the polynomial
+    /// Constructs a polynomial with the given coefficients
     /// The coefficients go from left to right, where x^0 at coeffs[0]
-    pub fn from_slice(coeffs: &[u8]) -> GaloisPolynomial {
-        Self {
-            coeffs: coeffs.iter().map(|val| Coeff(*val)).collect(),
-        }
-    }
-
-    /// Constructs a polynomial with the coefficients in the vec
-    /// The coefficients go from left to right, where x^0 at coeffs[0]
-    pub fn with_vec(coeffs: Vec<u8>) -> GaloisPolynomial {
-        Self {
-            coeffs: coeffs.into_iter().map(Coeff).collect(),
-        }
+    pub fn from_coeffs(coeffs: Vec<F>) -> Self {
+        Self { coeffs }
     }
 
     /// Reconstructs a polynomial from the given points
-    pub fn from_points(points: &[(u8, u8)]) -> GaloisPolynomial {
+    pub fn interpolate(points: &[(F, F)]) -> Self {
         let mut lagrange_polys = Vec::with_capacity(points.len());
         let mut temp_polys = Vec::with_capacity(points.len());
 
-        let points: Vec<(_, _)> = points.iter().map(|(x, y)| (Coeff(*x), Coeff(*y))).collect();
         for i in 0..points.len() {
             for j in 0..points.len() {
                 if i != j {
-                    let mut curr = Self::with_vec(Vec::with_capacity(points.len()));
-                    curr.set_coeff(Coeff(1), 1);
-                    curr.set_coeff(points[j].0, 0);
-                    curr = curr.scale_divide(points[i].0 - points[j].0);
+                    let mut curr = Self::from_coeffs(Vec::with_capacity(points.len()));
+                    curr.set_coeff(F::one(), 1);
+                    curr.set_coeff(F::zero() - points[j].0.clone(), 0);
+                    curr = curr.scale_divide(points[i].0.clone() - points[j].0.clone());
                     temp_polys.push(curr);
                 }
             }
 
             // Now multiply all the current poly's together and push it onto langrange_polys
-            let initial_val = temp_polys[0].clone();
             lagrange_polys.push(
                 temp_polys
-                    .clone()
-                    .into_iter()
-                    .skip(1)
-                    .fold(initial_val, |acc, poly| acc.mult(poly))
-                    .scale(points[i].1),
+                    .drain(..)
+                    .fold(Self::from_coeffs(vec![F::one()]), |acc, poly| acc.mult(poly))
+                    .scale(points[i].1.clone()),
             );
-            temp_polys.clear();
         }
 
-        let initial_val = lagrange_polys[0].clone();
-
         lagrange_polys
             .into_iter()
-            .skip(1)
-            .fold(initial_val, |acc, poly| acc.add(poly))
+            .fold(Self::new(), |acc, poly| acc.add(poly))
     }
 
-    /// Calculates the y intercept of the polynomial formed by the given points
-    /// This is more efficient than completely reconstructing the polynomial and then calling
-    /// $get_y_val if you do not plan to use the reconstructed polynomial after getting the y
-    /// intercept.
-    pub fn get_y_intercept_from_points(points: &[(u8, u8)]) -> u8 {
-        let mut acc = Coeff(0);
-        let points: Vec<(_, _)> = points.iter().map(|(x, y)| (Coeff(*x), Coeff(*y))).collect();
-        for i in 0..points.len() {
-            let mut curr = Coeff(1);
-            for j in 0..points.len() {
-                if i != j {
-                    curr = curr * (points[j].0 / (points[i].0 - points[j].0));
+    /// Calculates the y-value at **x_val** of the polynomial formed by the given points, without
+    /// reconstructing the polynomial.
+    pub fn evaluate_from_points(points: &[(F, F)], x_val: &F) -> F {
+        let x_values: Vec<F> = points.iter().map(|(x, _)| x.clone()).collect();
+        Self::lagrange_basis(&x_values, x_val)
+            .into_iter()
+            .zip(points.iter())
+            .fold(F::zero(), |acc, (weight, (_, y))| acc + (weight * y.clone()))
+    }
+
+    /// Calculates the value of each Lagrange basis polynomial for the given x-values at
+    /// **x_val**.
+    ///
+    /// The y-value at **x_val** of the polynomial running through points with these x-values is
+    /// then the sum of each point's y-value multiplied by the weight at the same index.
+    ///
+    /// Panics if any of the x-values are repeated.
+    pub fn lagrange_basis(x_values: &[F], x_val: &F) -> Vec<F> {
+        (0..x_values.len())
+            .map(|i| {
+                let mut num = F::one();
+                let mut denom = F::one();
+                for j in 0..x_values.len() {
+                    if i != j {
+                        num = num * (x_val.clone() - x_values[j].clone());
+                        denom = denom * (x_values[i].clone() - x_values[j].clone());
+                    }
                 }
-            }
-            acc = acc + (curr * points[i].1);
-        }
-        *acc
+                num * denom.inv().expect("x-values must be distinct")
+            })
+            .collect()
     }
 
     /// Sets the coefficient at the given index to the given co
-    pub fn set_coeff(&mut self, co: Coeff, index: usize) {
+    pub fn set_coeff(&mut self, co: F, index: usize) {
         if self.coeffs.len() < index + 1 {
-            self.coeffs.resize_with(index + 1, || Coeff(0));
+            self.coeffs.resize_with(index + 1, F::zero);
         }
 
         self.coeffs[index] = co;
     }
 
     /// Returns a copy of the coefficient at the specified index
-    pub fn get_coeff(&self, index: usize) -> Coeff {
-        if index + 1 < self.coeffs.len() {
-            self.coeffs[index]
+    pub fn get_coeff(&self, index: usize) -> F {
+        if index < self.coeffs.len() {
+            self.coeffs[index].clone()
         } else {
-            Coeff(0)
+            F::zero()
         }
     }
 
     /// Scales the polynomial by multiplying by the co
-    pub fn scale(mut self, co: Coeff) -> Self {
-        for i in 0..self.coeffs.len() {
-            self.coeffs[i] = self.coeffs[i] * co;
+    pub fn scale(mut self, co: F) -> Self {
+        for coeff in self.coeffs.iter_mut() {
+            *coeff = coeff.clone() * co.clone();
         }
         self
     }
 
     /// Scales the polynomial by dividing by the co
-    pub fn scale_divide(mut self, co: Coeff) -> Self {
-        for i in 0..self.coeffs.len() {
-            self.set_coeff(self.get_coeff(i) / co, i);
-        }
-        self
+    ///
+    /// Panics if co is zero.
+    pub fn scale_divide(self, co: F) -> Self {
+        self.scale(co.inv().expect("division by zero"))
     }
 
     /// Multiplies two polynomial together
     pub fn mult(self, rhs: Self) -> Self {
-        let mut prod = Self::with_vec(Vec::with_capacity(self.coeffs.len() + rhs.coeffs.len()));
-        for lhs_coeff in self.coeffs {
-            for (i, rhs_coeff) in rhs.coeffs.clone().into_iter().enumerate() {
-                prod.set_coeff(prod.get_coeff(i) + (rhs_coeff * lhs_coeff), i);
+        let mut prod =
+            Self::from_coeffs(Vec::with_capacity(self.coeffs.len() + rhs.coeffs.len()));
+        for (i, lhs_coeff) in self.coeffs.into_iter().enumerate() {
+            for (j, rhs_coeff) in rhs.coeffs.iter().enumerate() {
+                prod.set_coeff(
+                    prod.get_coeff(i + j) + (rhs_coeff.clone() * lhs_coeff.clone()),
+                    i + j,
+                );
             }
         }
         prod
     }
 
+    /// Divides the polynomial by **divisor**, returning the quotient and the remainder.
+    ///
+    /// Returns None if **divisor** is the zero polynomial.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let divisor_degree = divisor.degree()?;
+        let lead_inv = divisor.coeffs[divisor_degree].inv()?;
+        let mut rem = self.coeffs.clone();
+        let mut quot = Self::new();
+
+        // Long division, eliminating the highest remaining term each step
+        for i in (divisor_degree..rem.len()).rev() {
+            let factor = rem[i].clone() * lead_inv.clone();
+            if factor.is_zero() {
+                continue;
+            }
+            for (j, co) in divisor.coeffs[..=divisor_degree].iter().enumerate() {
+                let index = i - divisor_degree + j;
+                rem[index] = rem[index].clone() - (factor.clone() * co.clone());
+            }
+            quot.set_coeff(factor, i - divisor_degree);
+        }
+        rem.truncate(divisor_degree);
+
+        Some((quot, Self { coeffs: rem }))
+    }
+
+    /// Returns the degree of the polynomial, or None if it is the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.iter().rposition(|co| !co.is_zero())
+    }
+
+    /// Finds the polynomial with fewer than **num_coeffs** coefficients that runs through all but
+    /// at most (points.len() - num_coeffs) / 2 of the given points, using the Berlekamp-Welch
+    /// algorithm.
+    ///
+    /// Returns None if there are not enough points, or if there is no such polynomial because too
+    /// many of the points are incorrect.
+    pub fn interpolate_with_errors(points: &[(F, F)], num_coeffs: usize) -> Option<Self> {
+        if num_coeffs == 0 || points.len() < num_coeffs {
+            return None;
+        }
+        let max_errors = (points.len() - num_coeffs) / 2;
+        let num_q_coeffs = max_errors + num_coeffs;
+
+        // Solve for the error locator E(x) = x^e + ... + e_0 and Q(x) = E(x)P(x) using
+        // Q(x_i) - y_i * (E(x_i) - x_i^e) = y_i * x_i^e for every point, where the unknowns are
+        // Q's coefficients followed by E's coefficients, excluding its leading 1.
+        let mut matrix = Vec::with_capacity(points.len());
+        let mut rhs = Vec::with_capacity(points.len());
+        for (x, y) in points {
+            let mut row = Vec::with_capacity(num_q_coeffs + max_errors);
+            let mut x_pow = F::one();
+            for _ in 0..num_q_coeffs {
+                row.push(x_pow.clone());
+                x_pow = x_pow * x.clone();
+            }
+            let mut x_pow = F::one();
+            for _ in 0..max_errors {
+                row.push(F::zero() - (y.clone() * x_pow.clone()));
+                x_pow = x_pow * x.clone();
+            }
+            matrix.push(row);
+            rhs.push(y.clone() * x_pow);
+        }
+        let solution = solve_linear_system(matrix, rhs)?;
+
+        let q = Self {
+            coeffs: solution[..num_q_coeffs].to_vec(),
+        };
+        let mut e = Self {
+            coeffs: solution[num_q_coeffs..].to_vec(),
+        };
+        e.set_coeff(F::one(), max_errors);
+
+        let (poly, rem) = q.div_rem(&e)?;
+        if rem.degree().is_some() || poly.degree().unwrap_or(0) >= num_coeffs {
+            return None;
+        }
+
+        let num_errors = points
+            .iter()
+            .filter(|(x, y)| poly.evaluate(x) != *y)
+            .count();
+        if num_errors > max_errors {
+            return None;
+        }
+        Some(poly)
+    }
+
     /// Adds two polynomials together
     pub fn add(mut self, rhs: Self) -> Self {
         for (i, coeff) in rhs.coeffs.into_iter().enumerate() {
@@ -191,17 +295,94 @@ impl GaloisPolynomial {
     }
 
     /// Calculates the y-value given an x-value
-    pub fn get_y_value(&self, x_val: u8) -> u8 {
-        let x_val_coeff = Coeff(x_val);
+    pub fn evaluate(&self, x_val: &F) -> F {
         // This needs to be reversed since we are assuming the y-intercept in the field is the
         // left-most byte rather than the right-most.
-        *(&self.coeffs)
+        self.coeffs
             .iter()
             .rev()
-            .fold(Coeff(0u8), |acc, co| (acc * x_val_coeff) + *co)
+            .fold(F::zero(), |acc, co| (acc * x_val.clone()) + co.clone())
+    }
+}
+
+impl<F: Field> Default for GaloisPolynomial<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GaloisPolynomial<Coeff> {
+    /// Copies the values from the coeffs slice into a vec as the coefficients of the polynomial
+    /// The coefficients go from left to right, where x^0 at coeffs[0]
+    pub fn from_slice(coeffs: &[u8]) -> Self {
+        Self {
+            coeffs: coeffs.iter().map(|val| Coeff(*val)).collect(),
+        }
+    }
+
+    /// Constructs a polynomial with the coefficients in the vec
+    /// The coefficients go from left to right, where x^0 at coeffs[0]
+    pub fn with_vec(coeffs: Vec<u8>) -> Self {
+        Self {
+            coeffs: coeffs.into_iter().map(Coeff).collect(),
+        }
+    }
+
+    /// Reconstructs a polynomial from the given points
+    pub fn from_points(points: &[(u8, u8)]) -> Self {
+        Self::interpolate(&to_coeff_points(points))
+    }
+
+    /// Calculates the y intercept of the polynomial formed by the given points
+    /// This is more efficient than completely reconstructing the polynomial and then calling
+    /// $get_y_val if you do not plan to use the reconstructed polynomial after getting the y
+    /// intercept.
+    pub fn get_y_intercept_from_points(points: &[(u8, u8)]) -> u8 {
+        Self::get_y_value_from_points(points, 0)
+    }
+
+    /// Calculates the y-value at **x_val** of the polynomial formed by the given points, without
+    /// reconstructing the polynomial.
+    pub fn get_y_value_from_points(points: &[(u8, u8)], x_val: u8) -> u8 {
+        *Self::evaluate_from_points(&to_coeff_points(points), &Coeff(x_val))
+    }
+
+    /// Calculates the value of each Lagrange basis polynomial for the given x-values at
+    /// **x_val**.
+    ///
+    /// See [GaloisPolynomial::lagrange_basis]
+    pub fn lagrange_weights(x_values: &[u8], x_val: u8) -> Vec<Coeff> {
+        let x_values: Vec<Coeff> = x_values.iter().map(|x| Coeff(*x)).collect();
+        Self::lagrange_basis(&x_values, &Coeff(x_val))
+    }
+
+    /// Finds the polynomial that runs through all but the incorrect points.
+    ///
+    /// See [GaloisPolynomial::interpolate_with_errors]
+    pub fn from_points_with_errors(points: &[(u8, u8)], num_coeffs: usize) -> Option<Self> {
+        Self::interpolate_with_errors(&to_coeff_points(points), num_coeffs)
+    }
+
+    /// Calculates the y-value given an x-value
+    pub fn get_y_value(&self, x_val: u8) -> u8 {
+        *self.evaluate(&Coeff(x_val))
+    }
+}
+
+fn to_coeff_points(points: &[(u8, u8)]) -> Vec<(Coeff, Coeff)> {
+    points.iter().map(|(x, y)| (Coeff(*x), Coeff(*y))).collect()
+}
+
+impl<F: Field> PartialEq for GaloisPolynomial<F> {
+    fn eq(&self, other: &Self) -> bool {
+        // Trailing zero coefficients don't change the polynomial
+        (0..self.coeffs.len().max(other.coeffs.len()))
+            .all(|i| self.get_coeff(i) == other.get_coeff(i))
     }
 }
 
+impl<F: Field> Eq for GaloisPolynomial<F> {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -223,7 +404,50 @@ mod test {
         let poly_2 = GaloisPolynomial::from_points(points.as_slice());
         let y0 = GaloisPolynomial::get_y_intercept_from_points(points.as_slice());
 
-        assert_eq!(poly.get_y_value(0), poly_2.get_y_value(0));
+        assert_eq!(poly, poly_2);
         assert_eq!(poly.get_y_value(0), y0);
     }
+
+    #[test]
+    fn div_rem_gf() {
+        let divisor = GaloisPolynomial::with_vec(vec![3, 1]);
+        let quot = GaloisPolynomial::with_vec(vec![5, 128, 8]);
+        let rem = GaloisPolynomial::with_vec(vec![77]);
+        let poly = quot.clone().mult(divisor.clone()).add(rem.clone());
+
+        assert_eq!((quot, rem), poly.div_rem(&divisor).unwrap());
+        assert!(poly.div_rem(&GaloisPolynomial::new()).is_none());
+    }
+
+    #[test]
+    fn from_points_with_errors_gf() {
+        let poly = GaloisPolynomial::with_vec(vec![5, 128, 8]);
+        let mut points: Vec<(_, _)> = (1..=7).map(|x| (x, poly.get_y_value(x))).collect();
+        points[1].1 ^= 0x55;
+        points[5].1 ^= 0x01;
+
+        assert_eq!(
+            Some(poly),
+            GaloisPolynomial::from_points_with_errors(&points, 3)
+        );
+
+        points[3].1 ^= 0x10;
+        assert_eq!(None, GaloisPolynomial::from_points_with_errors(&points, 3));
+    }
+
+    #[test]
+    fn get_y_value_from_points_gf() {
+        let poly = GaloisPolynomial::with_vec(vec![5, 128, 8]);
+        let points: Vec<(_, _)> = [3, 9, 200]
+            .into_iter()
+            .map(|x| (x, poly.get_y_value(x)))
+            .collect();
+
+        for x in [0, 1, 9, 77, 255] {
+            assert_eq!(
+                poly.get_y_value(x),
+                GaloisPolynomial::get_y_value_from_points(&points, x)
+            );
+        }
+    }
 }
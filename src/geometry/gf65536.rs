@@ -0,0 +1,153 @@
+use super::Field;
+use lazy_static::*;
+use rand::RngCore;
+use std::ops::{Add, Deref, Div, Mul, Sub};
+
+// x^16 + x^12 + x^3 + x + 1, which is primitive so x generates every non-zero element
+const PRIMITIVE_POLY: u32 = 0x1100B;
+const ORDER: usize = 65535;
+
+struct Tables {
+    // exp[i] = x^i, doubled in length so that exp[log[a] + log[b]] never needs a reduction
+    exp: Vec<u16>,
+    log: Vec<u16>,
+}
+
+lazy_static! {
+    static ref TABLES: Tables = {
+        let mut exp = vec![0u16; 2 * ORDER];
+        let mut log = vec![0u16; ORDER + 1];
+        let mut curr: u32 = 1;
+        for i in 0..ORDER {
+            exp[i] = curr as u16;
+            exp[i + ORDER] = curr as u16;
+            log[curr as usize] = i as u16;
+            curr <<= 1;
+            if curr & 0x10000 != 0 {
+                curr ^= PRIMITIVE_POLY;
+            }
+        }
+        Tables { exp, log }
+    };
+}
+
+/// A wrapper around u16, used to implement arithmetic operations over GF(65536)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gf65536(pub u16);
+
+impl Deref for Gf65536 {
+    type Target = u16;
+
+    fn deref(&self) -> &u16 {
+        &self.0
+    }
+}
+
+impl From<u16> for Gf65536 {
+    fn from(source: u16) -> Gf65536 {
+        Gf65536(source)
+    }
+}
+
+// Addition and subtraction are both XOR since the field has characteristic 2
+impl Add for Gf65536 {
+    type Output = Gf65536;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, rhs: Gf65536) -> Gf65536 {
+        Gf65536(self.0 ^ rhs.0)
+    }
+}
+impl Sub for Gf65536 {
+    type Output = Gf65536;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Gf65536) -> Gf65536 {
+        Gf65536(self.0 ^ rhs.0)
+    }
+}
+impl Mul for Gf65536 {
+    type Output = Gf65536;
+    fn mul(self, rhs: Gf65536) -> Gf65536 {
+        if self.0 == 0 || rhs.0 == 0 {
+            return Gf65536(0);
+        }
+        let log_sum = TABLES.log[self.0 as usize] as usize + TABLES.log[rhs.0 as usize] as usize;
+        Gf65536(TABLES.exp[log_sum])
+    }
+}
+impl Div for Gf65536 {
+    type Output = Gf65536;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Gf65536) -> Gf65536 {
+        self * rhs.inv().expect("division by zero")
+    }
+}
+
+impl Field for Gf65536 {
+    const ENCODED_LEN: usize = 2;
+    const MAX_SHARES: u64 = ORDER as u64;
+
+    fn zero() -> Self {
+        Gf65536(0)
+    }
+
+    fn one() -> Self {
+        Gf65536(1)
+    }
+
+    fn inv(&self) -> Option<Self> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(Gf65536(
+                TABLES.exp[ORDER - TABLES.log[self.0 as usize] as usize],
+            ))
+        }
+    }
+
+    fn random(rng: &mut dyn RngCore) -> Self {
+        let mut bytes = [0u8; 2];
+        rng.fill_bytes(&mut bytes);
+        Gf65536(u16::from_be_bytes(bytes))
+    }
+
+    fn from_index(index: u64) -> Self {
+        Gf65536(index as u16)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        <[u8; 2]>::try_from(bytes)
+            .ok()
+            .map(|bytes| Gf65536(u16::from_be_bytes(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generator_is_primitive() {
+        let mut seen = vec![false; ORDER + 1];
+        for val in &TABLES.exp[..ORDER] {
+            assert!(!seen[*val as usize]);
+            seen[*val as usize] = true;
+        }
+        assert!(!seen[0]);
+    }
+
+    #[test]
+    fn arithmetic() {
+        for val in [1u16, 2, 3, 0x8000, 0x1234, 0xffff] {
+            let a = Gf65536(val);
+            assert_eq!(Gf65536::one(), a * a.inv().unwrap());
+            assert_eq!(a, (a * Gf65536(0xbeef)) / Gf65536(0xbeef));
+            assert_eq!(Some(a), Gf65536::from_bytes(&a.to_bytes()));
+        }
+        // x^15 * x = x^16 = x^12 + x^3 + x + 1
+        assert_eq!(Gf65536(0x100B), Gf65536(0x8000) * Gf65536(2));
+    }
+}
@@ -0,0 +1,93 @@
+use super::Field;
+
+/// Solves the system of linear equations `matrix * solution = rhs` over a finite field using
+/// Gaussian elimination.
+///
+/// Each row of **matrix** holds the coefficients of one equation, and the entry at the same
+/// index in **rhs** is its right hand side. If the system has more than one solution, the one
+/// with every free variable set to 0 is returned.
+///
+/// Returns None if the system has no solution.
+pub fn solve_linear_system<F: Field>(mut matrix: Vec<Vec<F>>, mut rhs: Vec<F>) -> Option<Vec<F>> {
+    let num_vars = matrix.first().map_or(0, |row| row.len());
+    let mut pivot_cols = Vec::with_capacity(num_vars);
+    let mut pivot_row = 0;
+
+    for col in 0..num_vars {
+        let found = (pivot_row..matrix.len()).find(|row| !matrix[*row][col].is_zero());
+        let Some(found) = found else {
+            continue;
+        };
+        matrix.swap(pivot_row, found);
+        rhs.swap(pivot_row, found);
+
+        // Scale the pivot to 1, then eliminate the column from every other row
+        let inv = matrix[pivot_row][col].inv()?;
+        for co in matrix[pivot_row].iter_mut() {
+            *co = co.clone() * inv.clone();
+        }
+        rhs[pivot_row] = rhs[pivot_row].clone() * inv;
+
+        let pivot = matrix[pivot_row].clone();
+        let pivot_rhs = rhs[pivot_row].clone();
+        for (row, (coeffs, rhs_co)) in matrix.iter_mut().zip(rhs.iter_mut()).enumerate() {
+            let factor = coeffs[col].clone();
+            if row == pivot_row || factor.is_zero() {
+                continue;
+            }
+            for (co, pivot_co) in coeffs.iter_mut().zip(pivot.iter()) {
+                *co = co.clone() - (factor.clone() * pivot_co.clone());
+            }
+            *rhs_co = rhs_co.clone() - (factor * pivot_rhs.clone());
+        }
+
+        pivot_cols.push(col);
+        pivot_row += 1;
+    }
+
+    // Any remaining row is all zeros, so its right hand side must be too
+    if rhs[pivot_row..].iter().any(|co| !co.is_zero()) {
+        return None;
+    }
+
+    let mut solution = vec![F::zero(); num_vars];
+    for (row, col) in pivot_cols.into_iter().enumerate() {
+        solution[col] = rhs[row].clone();
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geometry::Coeff;
+
+    fn to_coeffs(vals: &[u8]) -> Vec<Coeff> {
+        vals.iter().map(|val| Coeff(*val)).collect()
+    }
+
+    #[test]
+    fn solve() {
+        let solution = to_coeffs(&[7, 200, 13]);
+        let matrix: Vec<Vec<Coeff>> = [[1, 2, 3], [9, 0, 1], [4, 4, 250]]
+            .iter()
+            .map(|row| to_coeffs(row))
+            .collect();
+        let rhs = matrix
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(solution.iter())
+                    .fold(Coeff(0), |acc, (a, b)| acc + (*a * *b))
+            })
+            .collect();
+
+        assert_eq!(Some(solution), solve_linear_system(matrix, rhs));
+    }
+
+    #[test]
+    fn inconsistent() {
+        let matrix = vec![to_coeffs(&[1, 1]), to_coeffs(&[1, 1])];
+        assert_eq!(None, solve_linear_system(matrix, to_coeffs(&[1, 2])));
+    }
+}
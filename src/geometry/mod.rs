@@ -1,16 +1,21 @@
+#[cfg_attr(not(feature = "constant_time"), allow(dead_code))]
+pub mod constant_time;
+pub mod field;
 #[allow(dead_code)]
 pub mod fraction;
 #[allow(dead_code)]
 pub mod galois_polynomial;
+pub mod gf65536;
+pub mod linear_system;
 pub mod point;
+pub mod prime_field;
 pub mod polynomial;
 pub mod term;
 pub mod utils;
 
 // Re-export the modules contents into this module
-pub use fraction::*;
+pub use field::*;
 pub use galois_polynomial::*;
-pub use point::*;
-pub use polynomial::*;
-pub use term::*;
-pub use utils::*;
+pub use gf65536::*;
+pub use linear_system::*;
+pub use prime_field::*;
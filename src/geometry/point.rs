@@ -2,8 +2,6 @@ use super::fraction::Fraction;
 use crate::{impl_binary_op, impl_binary_op_simple};
 use std::ops::{Add, Sub};
 
-#[allow(dead_code)]
-
 /// A point structure that uses fractional values so that it can represent whole and non-whole
 /// numbers without the need for truncating.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
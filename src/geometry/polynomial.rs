@@ -166,7 +166,7 @@ impl Polynomial {
     pub fn scale<T: Into<Fraction>>(mut self, scalar: T) -> Self {
         let scalar = scalar.into();
         for index in 0..self.terms.len() {
-            self.terms[index as usize] = &self.terms[index as usize] * scalar;
+            self.terms[index] = &self.terms[index] * scalar;
         }
         self
     }
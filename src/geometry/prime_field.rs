@@ -0,0 +1,162 @@
+use super::Field;
+use lazy_static::*;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rand::RngCore;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Sub};
+
+lazy_static! {
+    static ref P25519_MODULUS: BigUint = (BigUint::one() << 255u32) - 19u32;
+    static ref SECP256K1_ORDER: BigUint = BigUint::parse_bytes(
+        b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+        16
+    )
+    .unwrap();
+}
+
+/// A prime that can be used as the modulus of a [Fp].
+pub trait PrimeModulus: Clone + Debug + PartialEq {
+    /// The number of bytes needed to encode any element of the field
+    const ENCODED_LEN: usize;
+
+    /// The prime itself
+    fn modulus() -> &'static BigUint;
+}
+
+/// The prime 2^255 - 19, the base field of Curve25519
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct P25519;
+
+impl PrimeModulus for P25519 {
+    const ENCODED_LEN: usize = 32;
+
+    fn modulus() -> &'static BigUint {
+        &P25519_MODULUS
+    }
+}
+
+/// The order of the secp256k1 group, which is the field secp256k1 private keys are in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Secp256k1Order;
+
+impl PrimeModulus for Secp256k1Order {
+    const ENCODED_LEN: usize = 32;
+
+    fn modulus() -> &'static BigUint {
+        &SECP256K1_ORDER
+    }
+}
+
+/// An element of the prime field of integers modulo **M**
+///
+/// NOTE: The arithmetic is done with [BigUint], which is not constant-time, so the time taken can
+/// leak information about the values. The `constant_time` feature does not change this.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fp<M: PrimeModulus> {
+    value: BigUint,
+    modulus: PhantomData<M>,
+}
+
+impl<M: PrimeModulus> Fp<M> {
+    /// Constructs an element from the given value, reducing it modulo **M**
+    pub fn new(value: BigUint) -> Self {
+        Self {
+            value: value % M::modulus(),
+            modulus: PhantomData,
+        }
+    }
+
+    /// Returns the value of the element, which is always less than the modulus
+    pub fn value(&self) -> &BigUint {
+        &self.value
+    }
+}
+
+impl<M: PrimeModulus> Add for Fp<M> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.value + rhs.value)
+    }
+}
+impl<M: PrimeModulus> Sub for Fp<M> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        // Add the modulus first so the subtraction can't underflow
+        Self::new(self.value + M::modulus() - rhs.value)
+    }
+}
+impl<M: PrimeModulus> Mul for Fp<M> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.value * rhs.value)
+    }
+}
+
+impl<M: PrimeModulus> Field for Fp<M> {
+    const ENCODED_LEN: usize = M::ENCODED_LEN;
+    const MAX_SHARES: u64 = u64::MAX;
+
+    fn zero() -> Self {
+        Self::new(BigUint::zero())
+    }
+
+    fn one() -> Self {
+        Self::new(BigUint::one())
+    }
+
+    fn inv(&self) -> Option<Self> {
+        if self.value.is_zero() {
+            return None;
+        }
+        // By Fermat's little theorem, a^(p - 2) = a^-1 for any non-zero a
+        let exponent = M::modulus() - 2u32;
+        Some(Self::new(self.value.modpow(&exponent, M::modulus())))
+    }
+
+    fn random(rng: &mut dyn RngCore) -> Self {
+        // Reducing 16 extra bytes makes the bias from the modulo negligible
+        let mut bytes = vec![0u8; M::ENCODED_LEN + 16];
+        rng.fill_bytes(&mut bytes);
+        Self::new(BigUint::from_bytes_be(&bytes))
+    }
+
+    fn from_index(index: u64) -> Self {
+        Self::new(BigUint::from(index))
+    }
+
+    /// Encodes the element as big-endian bytes
+    fn to_bytes(&self) -> Vec<u8> {
+        let bytes = self.value.to_bytes_be();
+        let mut encoded = vec![0u8; M::ENCODED_LEN - bytes.len()];
+        encoded.extend_from_slice(&bytes);
+        encoded
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let value = BigUint::from_bytes_be(bytes);
+        if bytes.len() != M::ENCODED_LEN || &value >= M::modulus() {
+            return None;
+        }
+        Some(Self::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let minus_one = Fp::<P25519>::zero() - Fp::one();
+        assert_eq!(&(P25519::modulus() - 1u32), minus_one.value());
+        assert_eq!(Fp::zero(), minus_one.clone() + Fp::one());
+        assert_eq!(Fp::one(), minus_one.clone() * minus_one.clone());
+
+        let a = Fp::<Secp256k1Order>::from_index(123456789);
+        assert_eq!(Fp::one(), a.clone() * a.inv().unwrap());
+        assert_eq!(Some(a.clone()), Fp::from_bytes(&a.to_bytes()));
+        assert_eq!(None, Fp::<Secp256k1Order>::from_bytes(&[0xff; 32]));
+    }
+}
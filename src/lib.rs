@@ -1,5 +1,14 @@
 pub mod basic_sharing;
+pub mod batch;
+pub mod field;
 mod geometry;
 mod utils;
 pub mod wrapped_sharing;
 pub mod derived_share;
+pub mod vss;
+pub mod resharing;
+pub mod share_header;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+#[cfg(feature = "tokio")]
+pub mod async_sharing;
@@ -0,0 +1,364 @@
+//! Pipelined versions of [share_to_writables](crate::wrapped_sharing::share_to_writables) and
+//! [reconstruct_from_srcs](crate::wrapped_sharing::reconstruct_from_srcs) that share and
+//! reconstruct segments on a pool of worker threads.
+//!
+//! A batch of segments is read from the input, the segments are processed in parallel, and the
+//! results are written out in order before the next batch is read. The shares are byte-identical
+//! to the ones the sequential functions create with the same rng and segment size, since each
+//! segment is shared with its own rng seeded in order from the rng for the whole secret.
+//!
+//! Only the work within a batch runs in parallel. Reading and writing are done on the calling
+//! thread between batches and aren't overlapped with the sharing, since the srcs and dests
+//! aren't required to be [Send]. This helps most when sharing or reconstructing costs more than
+//! the I/O, such as with a high threshold or many shares.
+use crate::share_header::{HashAlgorithm, Scheme, ShareHeader};
+use crate::wrapped_sharing::{
+    read_share_starts, read_shares, share_with_header, write_shares, Error, SecretTrait,
+    SegmentReconstructor, SegmentSharer, SegmentTask, ShareOptions, READ_SEGMENT_SIZE,
+};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::io::{Read, Seek, Write};
+
+/// Configures the segment size and number of threads used by the pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineConfig {
+    /// The number of bytes of the secret that are shared or reconstructed by a single task
+    pub segment_size: usize,
+    /// The number of worker threads. 0 uses rayon's default, which is the number of CPUs.
+    pub threads: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            segment_size: READ_SEGMENT_SIZE,
+            threads: 0,
+        }
+    }
+}
+
+impl PipelineConfig {
+    fn build_pool(&self) -> Result<ThreadPool, Error> {
+        ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .build()
+            .map_err(Error::ThreadPoolError)
+    }
+}
+
+/// Shares the secret to the dests in the same format as
+/// [share_to_writables](crate::wrapped_sharing::share_to_writables), sharing the segments on
+/// a pool of worker threads.
+///
+/// secret will have rewind() called on it
+///
+/// **verify**: If true, a hash is calculated from the secret and placed at the end to be used
+///             to verify reconstruction of the secret.
+pub fn share_to_writables<'a, T: Read + Seek>(
+    secret: T,
+    dests: &mut Vec<Box<dyn Write + 'a>>,
+    shares_required: u8,
+    shares_to_create: u8,
+    verify: bool,
+    config: &PipelineConfig,
+) -> Result<(), Error> {
+    share_to_writables_with_options(
+        secret,
+        dests,
+        shares_required,
+        shares_to_create,
+        &ShareOptions::from_verify(verify),
+        config,
+    )
+}
+
+/// Same as [share_to_writables], with the hash and checksum set by **options**
+pub fn share_to_writables_with_options<'a, T: Read + Seek>(
+    mut secret: T,
+    dests: &mut Vec<Box<dyn Write + 'a>>,
+    shares_required: u8,
+    shares_to_create: u8,
+    options: &ShareOptions,
+    config: &PipelineConfig,
+) -> Result<(), Error> {
+    let mut rand = StdRng::from_entropy();
+    let header = ShareHeader::new(
+        Scheme::Gf256,
+        shares_required as u16,
+        secret.len()?,
+        options.hash_algorithm,
+        &mut rand,
+    );
+    share_with_header(dests, header, options.checksum, |dests| {
+        share_to_writables_with_rng(
+            secret,
+            dests,
+            shares_required,
+            shares_to_create,
+            options.hash_algorithm,
+            config,
+            &mut rand,
+        )
+    })
+}
+
+fn share_to_writables_with_rng<'a, T: Read + Seek>(
+    mut secret: T,
+    dests: &mut Vec<Box<dyn Write + 'a>>,
+    shares_required: u8,
+    shares_to_create: u8,
+    hash_algorithm: HashAlgorithm,
+    config: &PipelineConfig,
+    rand: &mut dyn RngCore,
+) -> Result<(), Error> {
+    let pool = config.build_pool()?;
+    let segment_size = config.segment_size.max(1);
+    secret.rewind()?;
+    if dests.len() < (shares_to_create as usize) {
+        // Not enough dests to share shares to
+        return Err(Error::NotEnoughWriteableDestinations(
+            dests.len(),
+            shares_to_create,
+        ));
+    }
+
+    let (mut sharer, starts) =
+        SegmentSharer::new(shares_required, shares_to_create, hash_algorithm, rand)?;
+    write_shares(dests, &starts)?;
+    let batch_len = pool.current_num_threads();
+    loop {
+        // The rng for each segment is seeded as it's read, before any work is handed out, so
+        // the order matches the sequential path
+        let mut tasks = Vec::with_capacity(batch_len);
+        while tasks.len() < batch_len {
+            let mut secret_segment = Vec::with_capacity(segment_size);
+            (&mut secret)
+                .take(segment_size as u64)
+                .read_to_end(&mut secret_segment)?;
+            if secret_segment.is_empty() {
+                break;
+            }
+            tasks.push(sharer.next_segment(secret_segment, rand));
+        }
+        if tasks.is_empty() {
+            break;
+        }
+        let shared = pool.install(|| {
+            tasks
+                .into_par_iter()
+                .map(SegmentTask::share)
+                .collect::<Result<Vec<Vec<Vec<u8>>>, Error>>()
+        })?;
+        for shares in shared {
+            write_shares(dests, &shares)?;
+        }
+    }
+
+    if let Some(hash) = sharer.finish(rand) {
+        // Now that all of the shares have been written to, share the hash to the dests
+        write_shares(dests, &hash.share()?)?;
+    }
+
+    // Flush writes to all dests to ensure all bytes are written
+    for dest in dests.iter_mut() {
+        dest.flush().ok();
+    }
+    Ok(())
+}
+
+/// Reconstructs the secret from srcs created by either [share_to_writables] or
+/// [share_to_writables](crate::wrapped_sharing::share_to_writables), reconstructing the
+/// segments on a pool of worker threads. The srcs should all read the same number of bytes.
+///
+/// Will rewind() secret
+///
+/// **src_len** MUST be an accurate length of the shares
+///
+/// **verify**: If true, a hash is assumed to exist at the end of the secret and will be used
+///             to verify secret reconstruction. If the shares have headers, the hash algorithm
+///             is taken from them instead, but they must still have a hash if this is true.
+pub fn reconstruct_from_srcs<'a, T: Read + Write + Seek>(
+    mut secret: T,
+    srcs: &mut Vec<Box<dyn Read + 'a>>,
+    src_len: u64,
+    verify: bool,
+    config: &PipelineConfig,
+) -> Result<(), Error> {
+    let pool = config.build_pool()?;
+    let segment_size = config.segment_size.max(1) as u64;
+    secret.rewind()?;
+
+    // The header if there is one, followed by the x value of each share
+    let (x_vals, body_len, hash_algorithm) = read_share_starts(srcs, src_len, verify)?;
+    let key_len = hash_algorithm.key_len();
+    let hash_len = hash_algorithm.digest_len();
+    let mut remaining = body_len
+        .checked_sub((key_len + hash_len) as u64)
+        .ok_or(Error::ReconstructionNotEqual)?;
+    let mut reconstructor =
+        SegmentReconstructor::new(&x_vals, hash_algorithm, &read_shares(srcs, key_len)?)?;
+
+    let batch_len = pool.current_num_threads();
+    while remaining > 0 {
+        let mut batch = Vec::with_capacity(batch_len);
+        while batch.len() < batch_len && remaining > 0 {
+            let num_bytes = remaining.min(segment_size);
+            batch.push(read_shares(srcs, num_bytes as usize)?);
+            remaining -= num_bytes;
+        }
+        let reconstructed = pool.install(|| {
+            batch
+                .par_iter()
+                .map(|segments| reconstructor.reconstruct(segments))
+                .collect::<Result<Vec<Vec<u8>>, _>>()
+        })?;
+        for segment in reconstructed {
+            reconstructor.update(&segment);
+            secret.write_all(&segment)?;
+        }
+    }
+    secret.flush().ok();
+    reconstructor.finish(&read_shares(srcs, hash_len)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrapped_sharing::share_to_writables_with_rng as sequential_share;
+    use std::io::Cursor;
+
+    #[test]
+    fn matches_sequential() {
+        let secret: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+        let config = PipelineConfig {
+            segment_size: 1000,
+            threads: 3,
+        };
+        let mut sequential: Vec<Vec<u8>> = vec![Vec::new(); 5];
+        let mut parallel: Vec<Vec<u8>> = vec![Vec::new(); 5];
+        {
+            let mut dests: Vec<Box<dyn Write>> = sequential
+                .iter_mut()
+                .map(|share| Box::new(share) as Box<dyn Write>)
+                .collect();
+            let mut rand = StdRng::seed_from_u64(7);
+            sequential_share(
+                Cursor::new(&secret),
+                &mut dests,
+                3,
+                5,
+                HashAlgorithm::Sha3_512Prefix,
+                1000,
+                &mut rand,
+            )
+            .unwrap();
+        }
+        {
+            let mut dests: Vec<Box<dyn Write>> = parallel
+                .iter_mut()
+                .map(|share| Box::new(share) as Box<dyn Write>)
+                .collect();
+            let mut rand = StdRng::seed_from_u64(7);
+            share_to_writables_with_rng(
+                Cursor::new(&secret),
+                &mut dests,
+                3,
+                5,
+                HashAlgorithm::Sha3_512Prefix,
+                &config,
+                &mut rand,
+            )
+            .unwrap();
+        }
+        assert_eq!(sequential, parallel);
+
+        let src_len = parallel[0].len() as u64;
+        let mut srcs: Vec<Box<dyn Read>> = parallel[1..4]
+            .iter()
+            .map(|share| Box::new(Cursor::new(share)) as Box<dyn Read>)
+            .collect();
+        let mut recon = Cursor::new(Vec::new());
+        reconstruct_from_srcs(&mut recon, &mut srcs, src_len, true, &config).unwrap();
+        assert_eq!(secret, recon.into_inner());
+        assert_eq!(
+            secret,
+            crate::wrapped_sharing::reconstruct(&parallel[2..], true).unwrap()
+        );
+    }
+
+    #[test]
+    fn with_options() {
+        let secret: Vec<u8> = (0..5000).map(|i| (i % 7) as u8).collect();
+        let config = PipelineConfig {
+            segment_size: 512,
+            threads: 2,
+        };
+        let mut shares: Vec<Vec<u8>> = vec![Vec::new(); 3];
+        {
+            let mut dests: Vec<Box<dyn Write>> = shares
+                .iter_mut()
+                .map(|share| Box::new(share) as Box<dyn Write>)
+                .collect();
+            let options = ShareOptions::default();
+            let secret = Cursor::new(&secret);
+            share_to_writables_with_options(secret, &mut dests, 2, 3, &options, &config).unwrap();
+        }
+        let header = ShareHeader::from_bytes(&shares[0]).unwrap();
+        assert!(header.has_checksum());
+        assert_eq!(HashAlgorithm::Blake3Mac, header.hash_algorithm);
+        let share_len = crate::share_header::HEADER_LEN as u64 + header.body_len();
+        assert_eq!(share_len, shares[0].len() as u64);
+        assert_eq!(
+            secret,
+            crate::wrapped_sharing::reconstruct(&shares[1..], false).unwrap()
+        );
+
+        let src_len = shares[0].len() as u64;
+        let mut srcs: Vec<Box<dyn Read>> = shares[..2]
+            .iter()
+            .map(|share| Box::new(Cursor::new(share)) as Box<dyn Read>)
+            .collect();
+        let mut recon = Cursor::new(Vec::new());
+        reconstruct_from_srcs(&mut recon, &mut srcs, src_len, false, &config).unwrap();
+        assert_eq!(secret, recon.into_inner());
+    }
+
+    #[test]
+    fn default_config() {
+        let secret = b"a short secret".to_vec();
+        let config = PipelineConfig::default();
+        let mut shares: Vec<Vec<u8>> = vec![Vec::new(); 3];
+        {
+            let mut dests: Vec<Box<dyn Write>> = shares
+                .iter_mut()
+                .map(|share| Box::new(share) as Box<dyn Write>)
+                .collect();
+            share_to_writables(Cursor::new(&secret), &mut dests, 2, 3, false, &config).unwrap();
+        }
+        let mut dests: Vec<Box<dyn Write>> = vec![Box::new(Vec::new()), Box::new(Vec::new())];
+        assert!(matches!(
+            share_to_writables(Cursor::new(&secret), &mut dests, 2, 3, false, &config),
+            Err(Error::NotEnoughWriteableDestinations(2, 3))
+        ));
+
+        let src_len = shares[0].len() as u64;
+        let srcs = || -> Vec<Box<dyn Read>> {
+            shares[1..]
+                .iter()
+                .map(|share| Box::new(Cursor::new(share)) as Box<dyn Read>)
+                .collect()
+        };
+        let mut recon = Cursor::new(Vec::new());
+        reconstruct_from_srcs(&mut recon, &mut srcs(), src_len, false, &config).unwrap();
+        assert_eq!(secret, recon.into_inner());
+
+        // The header says there is no hash, so there is nothing to verify
+        assert!(matches!(
+            reconstruct_from_srcs(Cursor::new(Vec::new()), &mut srcs(), src_len, true, &config),
+            Err(Error::NoHashToVerify)
+        ));
+    }
+}
@@ -0,0 +1,227 @@
+//! Enrollment of a new share holder, by issuing them a share with a new x-value.
+//!
+//! The share for x-value `k` is `f(k) = Σ λ_i(k) * y_i`, where the `λ_i` are the Lagrange weights
+//! of the contributing holders' x-values evaluated at `k`. At least as many holders as are
+//! required to reconstruct take part:
+//!
+//! 1. Every contributing holder creates a random mask for every other contributing holder with
+//!    [generate_masks], and privately sends each one their mask.
+//! 2. Every contributing holder calculates their masked contribution `λ_i(k) * y_i` with
+//!    [contribution], and privately sends it to the new holder.
+//! 3. The new holder adds the contributions together with [combine_contributions].
+//!
+//! Each mask is added into exactly two contributions, the one of the holder that created it and
+//! the one of the holder that received it, so they cancel out in the sum. The new holder only
+//! learns their own share, and no holder learns anything about another holder's share.
+use super::*;
+use crate::geometry::{Coeff, GaloisPolynomial};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// Creates a random mask for each of the other contributing holders.
+///
+/// Returns one mask per other holder, in the format (1-byte recipient X-value),(N-byte mask).
+/// Each mask is meant for the holder with the recipient x-value only.
+///
+/// **own_x:** The x-value of the share of the holder creating the masks
+///
+/// **x_values:** The x-values of the shares of every contributing holder, including **own_x**
+///
+/// **share_len:** The length of the shares, not including the x-value.
+///
+/// **rand:** The rng source for the masks. The default is StdRng::from_entropy()
+pub fn generate_masks(
+    own_x: u8,
+    x_values: &[u8],
+    share_len: usize,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    check_x_values(x_values)?;
+    if !x_values.contains(&own_x) {
+        return Err(Error::InvalidXValue(own_x));
+    }
+
+    let mut from_entropy: Box<dyn RngCore>;
+    let rand = match rand {
+        Some(rng) => rng,
+        None => {
+            from_entropy = Box::new(StdRng::from_entropy());
+            &mut from_entropy
+        }
+    };
+
+    Ok(x_values
+        .iter()
+        .filter(|x| **x != own_x)
+        .map(|x| {
+            let mut mask = vec![0u8; share_len + 1];
+            mask[0] = *x;
+            rand.fill_bytes(&mut mask[1..]);
+            mask
+        })
+        .collect())
+}
+
+/// Calculates a holder's masked contribution to the share for **new_x**.
+///
+/// Returns the contribution in the format (1-byte new X-value),(N-byte contribution)
+///
+/// **share:** The holder's share, in the format (1-byte X-value),(N-byte share)
+///
+/// **x_values:** The x-values of the shares of every contributing holder, including this one.
+/// This must be the same for every contributing holder.
+///
+/// **new_x:** The x-value of the share to create for the new holder
+///
+/// **sent_masks:** The masks this holder created with [generate_masks]
+///
+/// **received_masks:** The masks this holder received from the other contributing holders
+pub fn contribution(
+    share: &[u8],
+    x_values: &[u8],
+    new_x: u8,
+    sent_masks: &[Vec<u8>],
+    received_masks: &[Vec<u8>],
+) -> Result<Vec<u8>, Error> {
+    let (x, ys) = split_share(share)?;
+    check_x_values(x_values)?;
+    if new_x == 0 || x_values.contains(&new_x) {
+        return Err(Error::InvalidXValue(new_x));
+    }
+    let index = x_values
+        .iter()
+        .position(|x_val| *x_val == x)
+        .ok_or(Error::InvalidXValue(x))?;
+    let weight = GaloisPolynomial::lagrange_weights(x_values, new_x)[index];
+
+    let mut contribution = Vec::with_capacity(share.len());
+    contribution.push(new_x);
+    contribution.extend(ys.iter().map(|y| *(weight * Coeff(*y))));
+
+    let sent = sent_masks.iter().map(|mask| (mask, true));
+    let received = received_masks.iter().map(|mask| (mask, false));
+    for (mask, is_sent) in sent.chain(received) {
+        let (recipient_x, mask_ys) = split_share(mask)?;
+        // Sent masks must be for one of the other holders, received masks must be for this one
+        let valid_recipient = if is_sent {
+            recipient_x != x && x_values.contains(&recipient_x)
+        } else {
+            recipient_x == x
+        };
+        if !valid_recipient {
+            return Err(Error::MismatchedXValue {
+                expected: recipient_x,
+                found: x,
+            });
+        }
+        if mask.len() != share.len() {
+            return Err(Error::MismatchedLength {
+                expected: share.len(),
+                found: mask.len(),
+            });
+        }
+        add_assign(&mut contribution[1..], mask_ys);
+    }
+
+    Ok(contribution)
+}
+
+/// Adds together the contributions received by the new holder into their share.
+///
+/// Returns the new share in the format (1-byte X-value),(N-byte share)
+pub fn combine_contributions(contributions: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+    let first = contributions.first().ok_or(Error::EmptyShare)?;
+    let (new_x, _) = split_share(first)?;
+    let mut new_share = vec![0u8; first.len()];
+    new_share[0] = new_x;
+
+    for contribution in contributions {
+        let (x, ys) = split_share(contribution)?;
+        if x != new_x {
+            return Err(Error::MismatchedXValue {
+                expected: x,
+                found: new_x,
+            });
+        }
+        if contribution.len() != first.len() {
+            return Err(Error::MismatchedLength {
+                expected: first.len(),
+                found: contribution.len(),
+            });
+        }
+        add_assign(&mut new_share[1..], ys);
+    }
+
+    Ok(new_share)
+}
+
+/// Creates the share for **new_x** from the given shares at once, without masking.
+///
+/// This is for when a single party holds at least the number of shares required, see
+/// [generate_masks] for enrolling with shares held by separate parties.
+pub fn enroll(shares: &[Vec<u8>], new_x: u8) -> Result<Vec<u8>, Error> {
+    let x_values = shares
+        .iter()
+        .map(|share| split_share(share).map(|(x, _)| x))
+        .collect::<Result<Vec<u8>, Error>>()?;
+    let contributions = shares
+        .iter()
+        .map(|share| contribution(share, &x_values, new_x, &[], &[]))
+        .collect::<Result<Vec<Vec<u8>>, Error>>()?;
+    combine_contributions(&contributions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_sharing::{from_secrets_no_points, reconstruct_secrets_no_points};
+
+    #[test]
+    fn masked_enrollment() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        let secret = vec![10, 20, 30, 40, 50];
+        let shares = from_secrets_no_points(&secret, 3, 4, None, Some(&mut rand)).unwrap();
+        let holders = &shares[..3];
+        let x_values: Vec<u8> = holders.iter().map(|share| share[0]).collect();
+        let new_x = 42;
+
+        let masks: Vec<Vec<Vec<u8>>> = x_values
+            .iter()
+            .map(|x| generate_masks(*x, &x_values, secret.len(), Some(&mut rand)).unwrap())
+            .collect();
+        let contributions: Vec<Vec<u8>> = holders
+            .iter()
+            .enumerate()
+            .map(|(i, share)| {
+                let received: Vec<Vec<u8>> = masks
+                    .iter()
+                    .flatten()
+                    .filter(|mask| mask[0] == x_values[i])
+                    .cloned()
+                    .collect();
+                contribution(share, &x_values, new_x, &masks[i], &received).unwrap()
+            })
+            .collect();
+        let new_share = combine_contributions(&contributions).unwrap();
+
+        assert_eq!(new_share, enroll(holders, new_x).unwrap());
+        assert_eq!(
+            secret,
+            reconstruct_secrets_no_points(vec![
+                new_share,
+                shares[3].clone(),
+                shares[0].clone()
+            ])
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn existing_x_rejected() {
+        let shares = from_secrets_no_points(&[1, 2, 3], 2, 3, None, None).unwrap();
+        assert!(matches!(
+            enroll(&shares[..2], 2),
+            Err(Error::InvalidXValue(2))
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+//! Protocols for managing shares created with
+//! [from_secrets_no_points](crate::basic_sharing::from_secrets_no_points) without ever
+//! reconstructing the secret.
+//!
+//! All of the shares here use the format:
+//!
+//! (1-byte X-value),(N-byte share)
+use crate::geometry::{Coeff, GaloisPolynomial};
+use rand::RngCore;
+
+pub mod enrollment;
+pub mod redistribution;
+pub mod refresh;
+
+/// Splits a share into its x-value and y-values.
+pub(crate) fn split_share(share: &[u8]) -> Result<(u8, &[u8]), Error> {
+    match share.split_first() {
+        Some((0, _)) => Err(Error::InvalidXValue(0)),
+        Some((x, ys)) => Ok((*x, ys)),
+        None => Err(Error::EmptyShare),
+    }
+}
+
+/// Checks that none of the x-values are 0 or repeated.
+pub(crate) fn check_x_values(x_values: &[u8]) -> Result<(), Error> {
+    for (i, x) in x_values.iter().enumerate() {
+        if *x == 0 {
+            return Err(Error::InvalidXValue(*x));
+        }
+        if x_values[..i].contains(x) {
+            return Err(Error::DuplicateXValue(*x));
+        }
+    }
+    Ok(())
+}
+
+/// Creates a polynomial of degree **shares_required** - 1 with the given y-intercept and
+/// uniformly random coefficients for the rest.
+pub(crate) fn random_polynomial(
+    y_intercept: u8,
+    shares_required: u8,
+    rng: &mut dyn RngCore,
+) -> GaloisPolynomial {
+    let mut coeffs = vec![0u8; shares_required as usize];
+    rng.fill_bytes(&mut coeffs);
+    coeffs[0] = y_intercept;
+    GaloisPolynomial::with_vec(coeffs)
+}
+
+/// Adds the y-values of **rhs** to **lhs** over GF(256).
+pub(crate) fn add_assign(lhs: &mut [u8], rhs: &[u8]) {
+    for (l, r) in lhs.iter_mut().zip(rhs) {
+        *l = *(Coeff(*l) + Coeff(*r));
+    }
+}
+
+/// Local Error enum, used to report errors that occur while managing shares.
+#[derive(Debug)]
+pub enum Error {
+    EmptyShare,
+    /// The x-value is not allowed, which is the case for 0 since that's where the secret is
+    InvalidXValue(u8),
+    /// More than one share with this x-value was given
+    DuplicateXValue(u8),
+    /// A share, or a value meant for one, does not have the same length as the others
+    MismatchedLength { expected: usize, found: usize },
+    /// A value meant for the share with x-value `expected` was given for the share with x-value
+    /// `found`
+    MismatchedXValue { expected: u8, found: u8 },
+    OtherSharingError(crate::basic_sharing::Error),
+}
+
+impl From<crate::basic_sharing::Error> for Error {
+    fn from(source: crate::basic_sharing::Error) -> Self {
+        Error::OtherSharingError(source)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::EmptyShare => write!(f, "Share should not be empty"),
+            Error::InvalidXValue(x) => write!(f, "{} is not a valid x-value for a share", x),
+            Error::DuplicateXValue(x) => {
+                write!(f, "Share with x-value {} was given more than once", x)
+            }
+            Error::MismatchedLength { expected, found } => write!(
+                f,
+                "Mismatched share length. Expected: {}; Found: {}",
+                expected, found
+            ),
+            Error::MismatchedXValue { expected, found } => write!(
+                f,
+                "Value is meant for the share with x-value {}, but was given for {}",
+                expected, found
+            ),
+            Error::OtherSharingError(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
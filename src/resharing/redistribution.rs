@@ -0,0 +1,187 @@
+//! Redistribution of an existing sharing into a sharing with a different number of shares
+//! required and/or created.
+//!
+//! This follows the Desmedt-Jajodia approach. At least as many old share holders as were
+//! required to reconstruct each split their share into sub-shares of the new scheme with
+//! [create_subshares], and privately send each new share holder the sub-share meant for them.
+//! Each new holder then combines the sub-shares they received with [combine_subshares] into
+//! their new share. The secret is never assembled in one place.
+//!
+//! Every new holder MUST combine sub-shares from the exact same set of old holders, otherwise
+//! the new shares will not reconstruct the secret. Once the new shares have been created, the
+//! old shares should be deleted.
+use super::*;
+use crate::basic_sharing::{from_secrets_no_points, reconstruct_secrets_no_points};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// Splits an old share into sub-shares for a new sharing.
+///
+/// Returns one sub-share per new share, in the format
+/// (1-byte old X-value),(1-byte new X-value),(N-byte sub-share)
+///
+/// **share:** The old share, in the format (1-byte X-value),(N-byte share)
+///
+/// **new_shares_required:** The number of shares required to reconstruct in the new scheme
+///
+/// **new_shares_to_create:** The number of shares to create in the new scheme
+///
+/// **rand:** The rng source for the generated coefficients. The default is
+/// StdRng::from_entropy()
+pub fn create_subshares(
+    share: &[u8],
+    new_shares_required: u8,
+    new_shares_to_create: u8,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let (old_x, ys) = split_share(share)?;
+    if ys.is_empty() {
+        return Err(Error::EmptyShare);
+    }
+
+    Ok(
+        from_secrets_no_points(ys, new_shares_required, new_shares_to_create, None, rand)?
+            .into_iter()
+            .map(|subshare| {
+                let mut tagged = Vec::with_capacity(subshare.len() + 1);
+                tagged.push(old_x);
+                tagged.extend_from_slice(&subshare);
+                tagged
+            })
+            .collect(),
+    )
+}
+
+/// Combines the sub-shares received by a new share holder into their new share.
+///
+/// **subshares:** The sub-shares with the same new x-value, one from each old share holder,
+/// created by [create_subshares]
+///
+/// Returns the new share in the format (1-byte X-value),(N-byte share)
+pub fn combine_subshares(subshares: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+    let first = subshares.first().ok_or(Error::EmptyShare)?;
+    if first.len() < 3 {
+        return Err(Error::EmptyShare);
+    }
+    let new_x = first[1];
+    let mut old_x_values = Vec::with_capacity(subshares.len());
+
+    // The sub-shares are points on a polynomial with the old x-values, and the new share is the
+    // y-intercept of that polynomial, exactly like reconstructing the secret from the old shares
+    let mut old_shares = Vec::with_capacity(subshares.len());
+    for subshare in subshares {
+        if subshare.len() != first.len() {
+            return Err(Error::MismatchedLength {
+                expected: first.len(),
+                found: subshare.len(),
+            });
+        }
+        if subshare[1] != new_x {
+            return Err(Error::MismatchedXValue {
+                expected: subshare[1],
+                found: new_x,
+            });
+        }
+        old_x_values.push(subshare[0]);
+        let mut old_share = Vec::with_capacity(subshare.len() - 1);
+        old_share.push(subshare[0]);
+        old_share.extend_from_slice(&subshare[2..]);
+        old_shares.push(old_share);
+    }
+    check_x_values(&old_x_values)?;
+    if new_x == 0 {
+        return Err(Error::InvalidXValue(new_x));
+    }
+
+    let ys = reconstruct_secrets_no_points(old_shares)?;
+    let mut new_share = Vec::with_capacity(ys.len() + 1);
+    new_share.push(new_x);
+    new_share.extend(ys);
+    Ok(new_share)
+}
+
+/// Redistributes all of the given shares at once.
+///
+/// This is for when a single party holds at least the number of shares required, see
+/// [create_subshares] for redistributing shares held by separate parties.
+///
+/// *For the arguments, see [create_subshares]*
+pub fn redistribute(
+    shares: &[Vec<u8>],
+    new_shares_required: u8,
+    new_shares_to_create: u8,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let mut from_entropy: Box<dyn RngCore>;
+    let mut rand = match rand {
+        Some(rng) => rng,
+        None => {
+            from_entropy = Box::new(StdRng::from_entropy());
+            &mut from_entropy
+        }
+    };
+
+    let mut subshares_by_new_x: Vec<Vec<Vec<u8>>> =
+        vec![Vec::with_capacity(shares.len()); new_shares_to_create as usize];
+
+    for share in shares {
+        let subshares = create_subshares(
+            share,
+            new_shares_required,
+            new_shares_to_create,
+            Some(&mut rand),
+        )?;
+        for (received, subshare) in subshares_by_new_x.iter_mut().zip(subshares) {
+            received.push(subshare);
+        }
+    }
+
+    subshares_by_new_x
+        .iter()
+        .map(|received| combine_subshares(received))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_of_three_to_three_of_five() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        let secret = vec![10, 20, 30, 40, 50];
+        let shares = from_secrets_no_points(&secret, 2, 3, None, Some(&mut rand)).unwrap();
+
+        let new_shares = redistribute(&shares[1..], 3, 5, Some(&mut rand)).unwrap();
+        assert_eq!(5, new_shares.len());
+        assert_eq!(
+            secret,
+            reconstruct_secrets_no_points(new_shares[2..].to_vec()).unwrap()
+        );
+        assert_eq!(
+            secret,
+            reconstruct_secrets_no_points(vec![
+                new_shares[0].clone(),
+                new_shares[4].clone(),
+                new_shares[1].clone()
+            ])
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn mismatched_subshares() {
+        let shares = from_secrets_no_points(&[1, 2], 2, 2, None, None).unwrap();
+        let first = create_subshares(&shares[0], 2, 3, None).unwrap();
+        let second = create_subshares(&shares[1], 2, 3, None).unwrap();
+
+        assert!(matches!(
+            combine_subshares(&[first[0].clone(), second[1].clone()]),
+            Err(Error::MismatchedXValue { .. })
+        ));
+        assert!(matches!(
+            combine_subshares(&[first[0].clone(), first[0].clone()]),
+            Err(Error::DuplicateXValue(1))
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+//! Proactive share refresh.
+//!
+//! Each share holder creates an update with [generate_update], which is a sharing of a zero
+//! secret, and privately sends each of the other holders the part meant for their x-value. Every
+//! holder then adds all of the parts they received to their share with [apply_updates]. The new
+//! shares reconstruct to the same secret, but are independent of the old ones, so an attacker
+//! slowly collecting shares has to start over after every refresh. No holder sees the secret or
+//! any of the other shares at any point.
+//!
+//! After a refresh the old shares MUST be deleted. Old and new shares do not lie on the same
+//! polynomial, so mixing them during reconstruction results in an incorrect secret.
+use super::*;
+use crate::basic_sharing::check_share_counts;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// Creates an update for the shares with the given x-values by sharing a zero secret.
+///
+/// Returns one update per x-value, in the same order as **x_values**, each in the format
+/// (1-byte X-value),(N-byte update). Each update is meant for the holder of the share with that
+/// x-value only.
+///
+/// **x_values:** The x-values of all of the shares that are to be refreshed. A share not included
+/// here will no longer work with the refreshed shares.
+///
+/// **share_len:** The length of the shares, not including the x-value.
+///
+/// **shares_required:** The number of shares required to reconstruct the secret, this must match
+/// the original sharing.
+///
+/// **rand:** The rng source for the generated coefficients. The default is
+/// StdRng::from_entropy()
+pub fn generate_update(
+    x_values: &[u8],
+    share_len: usize,
+    shares_required: u8,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    check_x_values(x_values)?;
+    check_share_counts(shares_required, x_values.len() as u8)?;
+    if shares_required < 1 {
+        // A polynomial without coefficients has no y-intercept to fix to 0
+        return Err(crate::basic_sharing::Error::InvalidShareCounts {
+            shares_required: shares_required as usize,
+            shares_to_create: x_values.len(),
+        }
+        .into());
+    }
+
+    let mut from_entropy: Box<dyn RngCore>;
+    let rand = match rand {
+        Some(rng) => rng,
+        None => {
+            from_entropy = Box::new(StdRng::from_entropy());
+            &mut from_entropy
+        }
+    };
+
+    let mut updates: Vec<Vec<u8>> = x_values
+        .iter()
+        .map(|x| {
+            let mut update = Vec::with_capacity(share_len + 1);
+            update.push(*x);
+            update
+        })
+        .collect();
+
+    for _ in 0..share_len {
+        // The y-intercept is fixed to 0, so adding this to the shares leaves the secret as is
+        let poly = random_polynomial(0, shares_required, rand);
+        for (update, x) in updates.iter_mut().zip(x_values) {
+            update.push(poly.get_y_value(*x));
+        }
+    }
+
+    Ok(updates)
+}
+
+/// Adds the updates meant for this share to it, returning the refreshed share.
+///
+/// **share:** A share in the format (1-byte X-value),(N-byte share)
+///
+/// **updates:** The updates for this share's x-value, one from each share holder that took part
+/// in the refresh.
+pub fn apply_updates(share: &[u8], updates: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+    let (x, _) = split_share(share)?;
+    let mut refreshed = share.to_vec();
+
+    for update in updates {
+        let (update_x, deltas) = split_share(update)?;
+        if update_x != x {
+            return Err(Error::MismatchedXValue {
+                expected: update_x,
+                found: x,
+            });
+        }
+        if update.len() != share.len() {
+            return Err(Error::MismatchedLength {
+                expected: share.len(),
+                found: update.len(),
+            });
+        }
+        add_assign(&mut refreshed[1..], deltas);
+    }
+
+    Ok(refreshed)
+}
+
+/// Refreshes all of the given shares at once with a single update.
+///
+/// This is for when a single party holds all of the shares, such as when they are stored in
+/// different locations, see [generate_update] for refreshing shares held by separate parties.
+///
+/// *For the arguments, see [generate_update]*
+pub fn refresh_shares(
+    shares: &[Vec<u8>],
+    shares_required: u8,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let share_len = shares.first().ok_or(Error::EmptyShare)?.len();
+    let x_values = shares
+        .iter()
+        .map(|share| split_share(share).map(|(x, _)| x))
+        .collect::<Result<Vec<u8>, Error>>()?;
+    let updates = generate_update(&x_values, share_len.saturating_sub(1), shares_required, rand)?;
+
+    shares
+        .iter()
+        .zip(updates.iter())
+        .map(|(share, update)| apply_updates(share, std::slice::from_ref(update)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_sharing::{from_secrets_no_points, reconstruct_secrets_no_points};
+
+    #[test]
+    fn refresh_keeps_secret() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        let secret = vec![10, 20, 30, 40, 50];
+        let shares = from_secrets_no_points(&secret, 3, 5, None, Some(&mut rand)).unwrap();
+        let refreshed = refresh_shares(&shares, 3, Some(&mut rand)).unwrap();
+
+        assert_ne!(shares, refreshed);
+        assert_eq!(
+            secret,
+            reconstruct_secrets_no_points(refreshed[2..].to_vec()).unwrap()
+        );
+
+        // Old shares no longer work together with the new ones
+        let mixed = vec![shares[0].clone(), refreshed[1].clone(), refreshed[2].clone()];
+        assert_ne!(secret, reconstruct_secrets_no_points(mixed).unwrap());
+    }
+
+    #[test]
+    fn distributed_refresh() {
+        let secret = vec![1, 2, 3];
+        let shares = from_secrets_no_points(&secret, 2, 3, None, None).unwrap();
+        let x_values: Vec<u8> = shares.iter().map(|share| share[0]).collect();
+
+        // Every holder creates an update and sends the i-th part of it to the i-th holder
+        let updates: Vec<Vec<Vec<u8>>> = (0..shares.len())
+            .map(|_| generate_update(&x_values, secret.len(), 2, None).unwrap())
+            .collect();
+        let refreshed: Vec<Vec<u8>> = shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| {
+                let received: Vec<Vec<u8>> =
+                    updates.iter().map(|update| update[i].clone()).collect();
+                apply_updates(share, &received).unwrap()
+            })
+            .collect();
+
+        assert_eq!(
+            secret,
+            reconstruct_secrets_no_points(vec![refreshed[0].clone(), refreshed[2].clone()])
+                .unwrap()
+        );
+        assert!(matches!(
+            apply_updates(&shares[0], &updates[0][1..2]),
+            Err(Error::MismatchedXValue { .. })
+        ));
+    }
+
+    #[test]
+    fn no_shares_required() {
+        assert!(matches!(
+            generate_update(&[1, 2, 3], 4, 0, None),
+            Err(Error::OtherSharingError(
+                crate::basic_sharing::Error::InvalidShareCounts { .. }
+            ))
+        ));
+    }
+}
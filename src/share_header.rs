@@ -0,0 +1,494 @@
+//! The header placed at the start of every share created by
+//! [wrapped_sharing](crate::wrapped_sharing), which records how the secret was shared so that
+//! reconstruction doesn't need to be told.
+//!
+//! Format, with every integer big-endian:
+//!
+//! | Bytes | Field          |
+//! |-------|----------------|
+//! | 4     | [MAGIC]        |
+//! | 1     | version        |
+//! | 1     | scheme         |
+//! | 2     | threshold      |
+//! | 2     | share index    |
+//! | 8     | payload length |
+//! | 1     | hash algorithm |
+//! | 1     | flags          |
+//! | 16    | share-set id   |
+//!
+//! The rest of the share is the same as a headerless share, starting with its x-value, except
+//! that with [HashAlgorithm::Blake3Mac] the shared MAC key comes between the x-value and the
+//! secret. Since
+//! x-values are never 0, a GF(256) share that starts with the 0 byte of [MAGIC] can't be mistaken
+//! for a headerless share from an older version. A headerless GF(65536) share can start with 0,
+//! but its third byte is a padding flag of 0 or 1, so it never starts with all of [MAGIC].
+use rand::RngCore;
+
+/// Marks the start of a share with a header
+pub const MAGIC: [u8; 4] = *b"\0SSS";
+
+/// The latest version of the header, which is the one written when sharing
+pub const VERSION: u8 = 1;
+
+/// The number of bytes in an encoded [ShareHeader]
+pub const HEADER_LEN: usize = 36;
+
+/// The length of the random id shared by every share from the same sharing
+pub const SHARE_SET_ID_LEN: usize = 16;
+
+/// Set in [ShareHeader::flags] when a BLAKE3 checksum of the rest of the share is appended to
+/// the end of it
+pub const FLAG_CHECKSUM: u8 = 0b0000_0001;
+
+/// The length of the checksum appended when [FLAG_CHECKSUM] is set
+pub const CHECKSUM_LEN: usize = 32;
+
+/// The length of the key shared in front of the secret with [HashAlgorithm::Blake3Mac]
+pub const MAC_KEY_LEN: usize = 32;
+
+// Every flag this version knows about
+const KNOWN_FLAGS: u8 = FLAG_CHECKSUM;
+
+/// The field the secret was shared over, which determines the layout of the rest of the share
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    /// GF(256), a 1-byte x-value followed by 1-byte y-values
+    Gf256,
+    /// GF(65536), in the format of [from_secrets_wide](crate::basic_sharing::from_secrets_wide)
+    Gf65536,
+}
+
+/// The hash shared along with the secret to verify its reconstruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// No hash was shared
+    None,
+    /// SHA3-512 of the first 32 bytes of the secret
+    Sha3_512Prefix,
+    /// SHA3-512 of the whole secret
+    Sha3_512,
+    /// BLAKE3 of the whole secret
+    Blake3,
+    /// BLAKE3 of the whole secret keyed with a random key, which is shared in front of the
+    /// secret. Unlike the plain hashes, this can't be used to check guesses of the secret
+    /// without reconstructing the key, and the shares can't be tampered with to give a
+    /// different secret that still verifies without knowing the key.
+    Blake3Mac,
+}
+
+impl HashAlgorithm {
+    /// The number of bytes the hash adds to the end of the secret
+    pub fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::None => 0,
+            HashAlgorithm::Sha3_512Prefix | HashAlgorithm::Sha3_512 => 64,
+            HashAlgorithm::Blake3 | HashAlgorithm::Blake3Mac => 32,
+        }
+    }
+
+    /// The number of bytes the MAC key adds to the start of the secret
+    pub fn key_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Blake3Mac => MAC_KEY_LEN,
+            _ => 0,
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            HashAlgorithm::None => 0,
+            HashAlgorithm::Sha3_512Prefix => 1,
+            HashAlgorithm::Sha3_512 => 2,
+            HashAlgorithm::Blake3 => 3,
+            HashAlgorithm::Blake3Mac => 4,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            0 => Ok(HashAlgorithm::None),
+            1 => Ok(HashAlgorithm::Sha3_512Prefix),
+            2 => Ok(HashAlgorithm::Sha3_512),
+            3 => Ok(HashAlgorithm::Blake3),
+            4 => Ok(HashAlgorithm::Blake3Mac),
+            id => Err(Error::UnknownHashAlgorithm(id)),
+        }
+    }
+}
+
+/// Describes a share and the sharing it belongs to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareHeader {
+    pub version: u8,
+    pub scheme: Scheme,
+    /// The number of shares required to reconstruct the secret
+    pub threshold: u16,
+    /// The x-value of the share, which is repeated at the start of the rest of the share
+    pub index: u16,
+    /// The length of the secret in bytes, not including the hash
+    pub payload_len: u64,
+    pub hash_algorithm: HashAlgorithm,
+    /// Options that change the share layout, see [FLAG_CHECKSUM]
+    pub flags: u8,
+    /// Random bytes that are the same for every share from one sharing
+    pub share_set_id: [u8; SHARE_SET_ID_LEN],
+}
+
+impl ShareHeader {
+    /// Creates the header for a new sharing with a random share-set id. The index is set to 0,
+    /// and should be set for each share with [ShareHeader::for_index].
+    pub fn new(
+        scheme: Scheme,
+        threshold: u16,
+        payload_len: u64,
+        hash_algorithm: HashAlgorithm,
+        rand: &mut dyn RngCore,
+    ) -> Self {
+        let mut share_set_id = [0u8; SHARE_SET_ID_LEN];
+        rand.fill_bytes(&mut share_set_id);
+        Self {
+            version: VERSION,
+            scheme,
+            threshold,
+            index: 0,
+            payload_len,
+            hash_algorithm,
+            flags: 0,
+            share_set_id,
+        }
+    }
+
+    /// Returns a copy of this header for the share with the given x-value
+    pub fn for_index(&self, index: u16) -> Self {
+        Self {
+            index,
+            ..self.clone()
+        }
+    }
+
+    /// Returns true if a checksum is appended to the share
+    pub fn has_checksum(&self) -> bool {
+        self.flags & FLAG_CHECKSUM != 0
+    }
+
+    /// The number of bytes in the share after the header, including the x-value, MAC key, hash
+    /// and checksum
+    pub fn body_len(&self) -> u64 {
+        let checksum_len = if self.has_checksum() { CHECKSUM_LEN } else { 0 };
+        let hash_algorithm = self.hash_algorithm;
+        let extra_len = hash_algorithm.key_len() + hash_algorithm.digest_len() + checksum_len;
+        1 + self.payload_len + extra_len as u64
+    }
+
+    /// Returns true if the share starts with a header rather than an x-value
+    pub fn is_present(share: &[u8]) -> bool {
+        share.starts_with(&MAGIC)
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = self.version;
+        bytes[5] = match self.scheme {
+            Scheme::Gf256 => 0,
+            Scheme::Gf65536 => 1,
+        };
+        bytes[6..8].copy_from_slice(&self.threshold.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.index.to_be_bytes());
+        bytes[10..18].copy_from_slice(&self.payload_len.to_be_bytes());
+        bytes[18] = self.hash_algorithm.id();
+        bytes[19] = self.flags;
+        bytes[20..].copy_from_slice(&self.share_set_id);
+        bytes
+    }
+
+    /// Decodes the header from the first [HEADER_LEN] bytes of **bytes**
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        if bytes[..4] != MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        if bytes[4] == 0 || bytes[4] > VERSION {
+            return Err(Error::UnsupportedVersion(bytes[4]));
+        }
+        let scheme = match bytes[5] {
+            0 => Scheme::Gf256,
+            1 => Scheme::Gf65536,
+            id => return Err(Error::UnknownScheme(id)),
+        };
+        let hash_algorithm = HashAlgorithm::from_id(bytes[18])?;
+        if bytes[19] & !KNOWN_FLAGS != 0 {
+            return Err(Error::UnknownFlags(bytes[19]));
+        }
+        let mut share_set_id = [0u8; SHARE_SET_ID_LEN];
+        share_set_id.copy_from_slice(&bytes[20..HEADER_LEN]);
+        Ok(Self {
+            version: bytes[4],
+            scheme,
+            threshold: u16::from_be_bytes([bytes[6], bytes[7]]),
+            index: u16::from_be_bytes([bytes[8], bytes[9]]),
+            payload_len: u64::from_be_bytes(bytes[10..18].try_into().unwrap()),
+            hash_algorithm,
+            flags: bytes[19],
+            share_set_id,
+        })
+    }
+}
+
+/// Returns true if **share** has a header with [FLAG_CHECKSUM] set and the checksum at the end
+/// doesn't match the rest of the share.
+///
+/// Shares without a checksum are never corrupt. This includes shares whose header is too damaged
+/// to be read, see [corrupt_shares] to catch those by comparing against other shares.
+pub fn is_corrupt(share: &[u8]) -> bool {
+    checksum_matches(share) == Some(false)
+}
+
+/// Returns the indexes of the corrupt shares in **shares**.
+///
+/// As well as the shares with a checksum that doesn't match, if any of the shares have a
+/// matching checksum then every share without one is corrupt, since a share from the same
+/// sharing would have one. This catches shares whose header was damaged so that it can't be
+/// read or no longer has [FLAG_CHECKSUM] set.
+pub fn corrupt_shares<S: AsRef<[u8]>>(shares: &[S]) -> Vec<usize> {
+    let matches: Vec<Option<bool>> = shares
+        .iter()
+        .map(|share| checksum_matches(share.as_ref()))
+        .collect();
+    let any_checksums = matches.contains(&Some(true));
+    matches
+        .iter()
+        .enumerate()
+        .filter(|(_, matches)| match matches {
+            Some(matches) => !matches,
+            None => any_checksums,
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// Returns whether the checksum at the end of the share matches the rest of it, or None if the
+// share doesn't have a header with FLAG_CHECKSUM set
+fn checksum_matches(share: &[u8]) -> Option<bool> {
+    match ShareHeader::from_bytes(share) {
+        Ok(header) if header.has_checksum() => {
+            if share.len() < HEADER_LEN + CHECKSUM_LEN {
+                return Some(false);
+            }
+            let (rest, checksum) = share.split_at(share.len() - CHECKSUM_LEN);
+            Some(blake3::hash(rest).as_bytes() == checksum)
+        }
+        _ => None,
+    }
+}
+
+/// Checks that the headers all describe the same sharing over **scheme**, and that there are
+/// enough of them to reconstruct the secret.
+///
+/// Shares from different sharings are rejected with [Error::MismatchedShareSets] even if the
+/// rest of their headers match, since reconstructing from them would give garbage.
+///
+/// Returns the first header, which describes the secret for all of them.
+pub fn check_headers(headers: &[ShareHeader], scheme: Scheme) -> Result<&ShareHeader, Error> {
+    let first = headers.first().ok_or(Error::NotEnoughShares {
+        given: 0,
+        required: 1,
+    })?;
+    if first.scheme != scheme {
+        return Err(Error::WrongScheme(first.scheme));
+    }
+    let (matching, mismatched): (Vec<&ShareHeader>, Vec<&ShareHeader>) = headers
+        .iter()
+        .partition(|header| header.share_set_id == first.share_set_id);
+    if !mismatched.is_empty() {
+        return Err(Error::MismatchedShareSets {
+            matching: matching.iter().map(|header| header.index).collect(),
+            mismatched: mismatched.iter().map(|header| header.index).collect(),
+        });
+    }
+    for (i, header) in headers.iter().enumerate().skip(1) {
+        if header.version != first.version
+            || header.scheme != first.scheme
+            || header.threshold != first.threshold
+            || header.payload_len != first.payload_len
+            || header.hash_algorithm != first.hash_algorithm
+            || header.flags != first.flags
+        {
+            return Err(Error::MismatchedHeaders(i));
+        }
+    }
+    if headers.len() < first.threshold as usize {
+        return Err(Error::NotEnoughShares {
+            given: headers.len(),
+            required: first.threshold,
+        });
+    }
+    Ok(first)
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Truncated,
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    UnknownScheme(u8),
+    UnknownHashAlgorithm(u8),
+    UnknownFlags(u8),
+    WrongScheme(Scheme),
+    MismatchedHeaders(usize),
+    /// The share indexes with the same share-set id as the first share, and the ones without it
+    MismatchedShareSets {
+        matching: Vec<u16>,
+        mismatched: Vec<u16>,
+    },
+    NotEnoughShares { given: usize, required: u16 },
+    IndexMismatch { index: u16, x_value: u16 },
+    WrongShareLength { expected: u64, found: u64 },
+    MixedHeaders,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "Share is too short to contain a header"),
+            Error::InvalidMagic => write!(f, "Share header does not start with the magic bytes"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "Share header version {} is not supported", version)
+            }
+            Error::UnknownScheme(id) => write!(f, "Unknown sharing scheme id: {}", id),
+            Error::UnknownHashAlgorithm(id) => write!(f, "Unknown hash algorithm id: {}", id),
+            Error::UnknownFlags(flags) => write!(f, "Unknown flags set: {:#010b}", flags),
+            Error::WrongScheme(scheme) => write!(
+                f,
+                "Shares were created with the {:?} scheme, which this function can't reconstruct",
+                scheme
+            ),
+            Error::MismatchedHeaders(i) => write!(
+                f,
+                "The header of share {} does not match the header of the first share",
+                i
+            ),
+            Error::MismatchedShareSets {
+                matching,
+                mismatched,
+            } => write!(
+                f,
+                "Shares {:?} are from a different sharing than shares {:?}",
+                mismatched, matching
+            ),
+            Error::NotEnoughShares { given, required } => write!(
+                f,
+                "Not enough shares to reconstruct the secret. Given: {}, required: {}",
+                given, required
+            ),
+            Error::IndexMismatch { index, x_value } => write!(
+                f,
+                "Share header has index {} but the share has x-value {}",
+                index, x_value
+            ),
+            Error::WrongShareLength { expected, found } => write!(
+                f,
+                "Share should be {} bytes according to its header, but is {} bytes",
+                expected, found
+            ),
+            Error::MixedHeaders => write!(f, "Some of the shares have headers and some don't"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn round_trip() {
+        let mut rand = StdRng::seed_from_u64(5);
+        let header = ShareHeader::new(
+            Scheme::Gf65536,
+            300,
+            1 << 40,
+            HashAlgorithm::Sha3_512Prefix,
+            &mut rand,
+        )
+        .for_index(1000);
+        let bytes = header.to_bytes();
+        assert!(ShareHeader::is_present(&bytes));
+        assert_eq!(header, ShareHeader::from_bytes(&bytes).unwrap());
+
+        let mut bad_flags = bytes;
+        bad_flags[19] = 0b10;
+        assert!(matches!(
+            ShareHeader::from_bytes(&bad_flags),
+            Err(Error::UnknownFlags(0b10))
+        ));
+
+        let mut bad_version = bytes;
+        bad_version[4] = VERSION + 1;
+        assert!(matches!(
+            ShareHeader::from_bytes(&bad_version),
+            Err(Error::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            ShareHeader::from_bytes(&bytes[..HEADER_LEN - 1]),
+            Err(Error::Truncated)
+        ));
+
+        let hash_algorithms = [
+            HashAlgorithm::None,
+            HashAlgorithm::Sha3_512,
+            HashAlgorithm::Blake3,
+            HashAlgorithm::Blake3Mac,
+        ];
+        for hash_algorithm in hash_algorithms {
+            let header = ShareHeader::new(Scheme::Gf256, 2, 10, hash_algorithm, &mut rand);
+            assert_eq!(header, ShareHeader::from_bytes(&header.to_bytes()).unwrap());
+        }
+    }
+
+    #[test]
+    fn checks() {
+        let mut rand = StdRng::seed_from_u64(5);
+        let header = ShareHeader::new(Scheme::Gf256, 2, 10, HashAlgorithm::None, &mut rand);
+        let headers = vec![header.for_index(1), header.for_index(2)];
+        assert!(check_headers(&headers, Scheme::Gf256).is_ok());
+        assert!(matches!(
+            check_headers(&headers, Scheme::Gf65536),
+            Err(Error::WrongScheme(Scheme::Gf256))
+        ));
+        assert!(matches!(
+            check_headers(&headers[..1], Scheme::Gf256),
+            Err(Error::NotEnoughShares {
+                given: 1,
+                required: 2
+            })
+        ));
+
+        let other_set = ShareHeader::new(Scheme::Gf256, 2, 10, HashAlgorithm::None, &mut rand);
+        match check_headers(
+            &[header.for_index(1), other_set.for_index(2), header.for_index(3)],
+            Scheme::Gf256,
+        ) {
+            Err(Error::MismatchedShareSets {
+                matching,
+                mismatched,
+            }) => {
+                assert_eq!(vec![1, 3], matching);
+                assert_eq!(vec![2], mismatched);
+            }
+            result => panic!("Expected MismatchedShareSets, got {:?}", result),
+        }
+
+        let mut other = header.for_index(3);
+        other.payload_len = 11;
+        assert!(matches!(
+            check_headers(&[header.for_index(1), other], Scheme::Gf256),
+            Err(Error::MismatchedHeaders(1))
+        ));
+    }
+}
@@ -1,6 +1,8 @@
 use std::ops::Rem;
 /// Trait for calculating modulo with negative numbers. Rem/Modulo operations move out the negative
 /// number, but that is not always the needed output.
+// Nothing outside the tests calls natural_mod yet
+#[cfg_attr(not(test), allow(dead_code))]
 pub trait NaturalMod<T: Rem + Sized + Copy = Self>: Rem + Sized + Copy {
     type Output_: Sized + Copy;
 
@@ -17,9 +19,11 @@ impl<T: Rem<T, Output=T> + Sized + Copy + Add<T, Output=T>> NaturalMod for T {
     }
 }
 */
+#[cfg_attr(not(test), allow(dead_code))]
 fn floor(num: i64, div: i64) -> i64 {
     ((num as f64) / (div as f64)) as i64
 }
+#[cfg_attr(not(test), allow(dead_code))]
 fn ceiling(num: i64, div: i64) -> i64 {
     ((num.abs() + div - 1) / div) * num.signum()
 }
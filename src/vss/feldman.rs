@@ -0,0 +1,193 @@
+//! Feldman verifiable secret sharing.
+//!
+//! Alongside the shares, the dealer publishes a commitment `g^a` to every coefficient `a` of
+//! every polynomial used. Any share holder can then check that their share lies on the committed
+//! polynomial with [verify_share] without learning anything about the other shares.
+//!
+//! NOTE: The commitment to the y-intercept is `g^secret`, so anyone can check a guess of the
+//! secret against it. Only use this for secrets with high entropy, such as keys. See
+//! [pedersen] for secrets that can be guessed.
+use super::*;
+use crate::basic_sharing::check_share_counts;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// Marks [Commitments] as created by Feldman sharing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Feldman {}
+
+/// The commitments `g^a` to the coefficients used to create a set of Feldman shares.
+pub type Commitments = super::Commitments<Feldman>;
+
+/// A share of a secret, holding one y-value per [CHUNK_SIZE] chunk of the secret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub x: u8,
+    pub ys: Vec<Scalar>,
+}
+
+impl Share {
+    /// Encodes the share as (1-byte X-value),(32-byte y-value for each chunk)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + self.ys.len() * ELEMENT_SIZE);
+        bytes.push(self.x);
+        encode_scalars(&mut bytes, &self.ys);
+        bytes
+    }
+
+    /// Decodes a share encoded with [Share::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (x, ys) = bytes.split_first().ok_or(Error::InvalidEncoding)?;
+        Ok(Share {
+            x: *x,
+            ys: decode_scalars(ys)?,
+        })
+    }
+}
+
+/// Creates shares of the secret along with the commitments needed to verify them.
+///
+/// **secret:** The secret to be split into shares
+///
+/// **shares_required:** The number of shares required to recreate the secret
+///
+/// **shares_to_create:** The number of shares to create
+///
+/// **rand:** The rng source for the generated coefficients in the sharing process.
+/// The default is StdRng::from_entropy()
+///
+/// NOTE: Using predictable RNG can be a security risk. If unsure, use None.
+pub fn from_secrets(
+    secret: &[u8],
+    shares_required: u8,
+    shares_to_create: u8,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<(Vec<Share>, Commitments), Error> {
+    if secret.is_empty() {
+        return Err(crate::basic_sharing::Error::EmptySecretArray.into());
+    }
+    check_share_counts(shares_required, shares_to_create)?;
+
+    let mut from_entropy: Box<dyn RngCore>;
+    let rand = match rand {
+        Some(rng) => rng,
+        None => {
+            from_entropy = Box::new(StdRng::from_entropy());
+            &mut from_entropy
+        }
+    };
+
+    let polys: Vec<Vec<Scalar>> = secret_to_scalars(secret, rand)
+        .into_iter()
+        .map(|chunk| random_polynomial(chunk, shares_required, rand))
+        .collect();
+
+    let shares = (1..=shares_to_create)
+        .map(|x| Share {
+            x,
+            ys: polys.iter().map(|poly| evaluate(poly, x)).collect(),
+        })
+        .collect();
+
+    let commitments = Commitments::new(
+        secret.len(),
+        shares_required,
+        polys
+            .iter()
+            .map(|poly| poly.iter().map(RistrettoPoint::mul_base).collect())
+            .collect(),
+    );
+
+    Ok((shares, commitments))
+}
+
+/// Checks that the share lies on the polynomials committed to by **commitments**.
+///
+/// Returns true if the share is valid.
+pub fn verify_share(share: &Share, commitments: &Commitments) -> bool {
+    if share.x == 0 || share.ys.len() != commitments.chunks.len() {
+        return false;
+    }
+    share
+        .ys
+        .iter()
+        .zip(commitments.chunks.iter())
+        .all(|(y, chunk)| RistrettoPoint::mul_base(y) == evaluate_commitments(chunk, share.x))
+}
+
+/// Verifies each share against the commitments and then reconstructs the secret from them.
+///
+/// Returns [Error::InvalidShare] with the x-value of the first share that fails verification.
+pub fn reconstruct_secrets(shares: &[Share], commitments: &Commitments) -> Result<Vec<u8>, Error> {
+    reconstruct_verified(
+        shares,
+        commitments,
+        |share| (share.x, share.ys.as_slice()),
+        verify_share,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn share_verify_reconstruct() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        let secret: Vec<u8> = (0..100).collect();
+        let (shares, commitments) = from_secrets(&secret, 3, 5, Some(&mut rand)).unwrap();
+
+        for share in &shares {
+            assert!(verify_share(share, &commitments));
+        }
+
+        let recon = reconstruct_secrets(&shares[2..], &commitments).unwrap();
+        assert_eq!(secret, recon);
+    }
+
+    #[test]
+    fn tampered_share_detected() {
+        let secret = vec![10, 20, 30, 40, 50];
+        let (mut shares, commitments) = from_secrets(&secret, 2, 3, None).unwrap();
+        shares[1].ys[0] += Scalar::ONE;
+
+        assert!(!verify_share(&shares[1], &commitments));
+        assert!(matches!(
+            reconstruct_secrets(&shares, &commitments),
+            Err(Error::InvalidShare(2))
+        ));
+    }
+
+    #[test]
+    fn short_chunk_not_recoverable_from_commitments() {
+        // The last chunk holds a single byte, which on its own would take 256 tries to find
+        let mut secret = vec![0x5a; CHUNK_SIZE * 2 + 1];
+        secret[CHUNK_SIZE * 2] = 0xc7;
+        let (shares, commitments) = from_secrets(&secret, 2, 3, None).unwrap();
+
+        let last = commitments.chunks.last().unwrap()[0];
+        for guess in 0..=u8::MAX {
+            let mut bytes = [0u8; ELEMENT_SIZE];
+            bytes[0] = guess;
+            assert_ne!(RistrettoPoint::mul_base(&Scalar::from_bytes_mod_order(bytes)), last);
+        }
+        assert_eq!(secret, reconstruct_secrets(&shares, &commitments).unwrap());
+    }
+
+    #[test]
+    fn encoding() {
+        let secret = vec![1; 40];
+        let (shares, commitments) = from_secrets(&secret, 2, 2, None).unwrap();
+        let shares: Vec<Share> = shares
+            .iter()
+            .map(|share| Share::from_bytes(&share.to_bytes()).unwrap())
+            .collect();
+        let commitments = Commitments::from_bytes(&commitments.to_bytes()).unwrap();
+
+        assert_eq!(secret, reconstruct_secrets(&shares, &commitments).unwrap());
+    }
+}
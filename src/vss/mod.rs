@@ -0,0 +1,369 @@
+//! Verifiable secret sharing.
+//!
+//! Commitment schemes need the shares to live in the exponent of a prime-order group, which
+//! GF(256) cannot provide since its additive group has characteristic 2. The sharing in this
+//! module is therefore done over the scalar field of the Ristretto group (prime order
+//! 2^252 + 27742317777372353535851937790883648493) instead of bytewise. The secret is split into
+//! chunks of [CHUNK_SIZE] bytes, each of which is padded with random bytes and shared with its
+//! own polynomial.
+//!
+//! Shares created here are NOT compatible with the shares from
+//! [basic_sharing](crate::basic_sharing) or [wrapped_sharing](crate::wrapped_sharing).
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+use rand::RngCore;
+use std::marker::PhantomData;
+
+pub mod feldman;
+pub mod pedersen;
+
+/// The number of bytes of the secret packed into each scalar.
+///
+/// The next [PADDING_SIZE] bytes of the scalar are random, so that the commitment to a chunk
+/// can't be brute-forced even when the chunk is short, such as the last byte of a 32-byte key.
+pub const CHUNK_SIZE: usize = 16;
+
+/// The number of random bytes packed into each scalar after the chunk. Together with the chunk
+/// they take up 31 bytes, which always fit below the group order, so every chunk maps to a
+/// unique scalar.
+pub const PADDING_SIZE: usize = 15;
+
+/// The length of an encoded scalar or compressed group element.
+pub const ELEMENT_SIZE: usize = 32;
+
+/// The public commitments to the polynomials used to create a set of shares with the scheme
+/// **S**, see [feldman::Commitments] and [pedersen::Commitments].
+///
+/// These are meant to be published to every share holder, and are needed to both verify and
+/// reconstruct. The scheme is part of the type, so the commitments from one scheme can't be
+/// used with the shares from the other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commitments<S> {
+    pub(crate) secret_len: usize,
+    pub(crate) shares_required: u8,
+    // One list of commitments per chunk, with the commitment to x^0 at index 0
+    pub(crate) chunks: Vec<Vec<RistrettoPoint>>,
+    scheme: PhantomData<S>,
+}
+
+impl<S> Commitments<S> {
+    pub(crate) fn new(
+        secret_len: usize,
+        shares_required: u8,
+        chunks: Vec<Vec<RistrettoPoint>>,
+    ) -> Self {
+        Self {
+            secret_len,
+            shares_required,
+            chunks,
+            scheme: PhantomData,
+        }
+    }
+
+    /// The length of the secret that was shared
+    pub fn secret_len(&self) -> usize {
+        self.secret_len
+    }
+
+    /// The number of shares needed to reconstruct the secret
+    pub fn shares_required(&self) -> u8 {
+        self.shares_required
+    }
+
+    /// Encodes the commitments as
+    /// (8-byte secret length),(1-byte shares required),(32-byte point for each coefficient)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            9 + self.chunks.len() * self.shares_required as usize * ELEMENT_SIZE,
+        );
+        bytes.extend_from_slice(&(self.secret_len as u64).to_be_bytes());
+        bytes.push(self.shares_required);
+        for chunk in &self.chunks {
+            encode_points(&mut bytes, chunk);
+        }
+        bytes
+    }
+
+    /// Decodes commitments encoded with [Commitments::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < 9 {
+            return Err(Error::InvalidEncoding);
+        }
+        let mut secret_len = [0u8; 8];
+        secret_len.copy_from_slice(&bytes[..8]);
+        let secret_len =
+            usize::try_from(u64::from_be_bytes(secret_len)).map_err(|_| Error::InvalidEncoding)?;
+        let shares_required = bytes[8];
+        let points = decode_points(&bytes[9..])?;
+        let num_points = secret_len
+            .div_ceil(CHUNK_SIZE)
+            .checked_mul(shares_required as usize)
+            .ok_or(Error::InvalidEncoding)?;
+        if shares_required == 0 || points.len() != num_points {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(Self::new(
+            secret_len,
+            shares_required,
+            points
+                .chunks(shares_required as usize)
+                .map(|chunk| chunk.to_vec())
+                .collect(),
+        ))
+    }
+}
+
+/// Packs the secret into scalars, [CHUNK_SIZE] bytes at a time followed by [PADDING_SIZE]
+/// random bytes. The last chunk may be shorter.
+pub(crate) fn secret_to_scalars(secret: &[u8], rng: &mut dyn RngCore) -> Vec<Scalar> {
+    secret
+        .chunks(CHUNK_SIZE)
+        .map(|chunk| {
+            let mut bytes = [0u8; ELEMENT_SIZE];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            rng.fill_bytes(&mut bytes[CHUNK_SIZE..CHUNK_SIZE + PADDING_SIZE]);
+            Scalar::from_bytes_mod_order(bytes)
+        })
+        .collect()
+}
+
+/// Unpacks scalars created by [secret_to_scalars] back into a secret of **secret_len** bytes,
+/// dropping the padding.
+pub(crate) fn scalars_to_secret(scalars: &[Scalar], secret_len: usize) -> Vec<u8> {
+    let mut secret = Vec::with_capacity(secret_len);
+    for scalar in scalars {
+        let remaining = secret_len - secret.len();
+        let bytes = scalar.to_bytes();
+        secret.extend_from_slice(&bytes[..remaining.min(CHUNK_SIZE)]);
+    }
+    secret
+}
+
+/// Generates a uniformly random scalar from the given rng.
+pub(crate) fn random_scalar(rng: &mut dyn RngCore) -> Scalar {
+    let mut wide = [0u8; 64];
+    rng.fill_bytes(&mut wide);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+/// Creates a polynomial of degree **shares_required** - 1 with **secret** as the y-intercept and
+/// random coefficients for the rest. The coefficients go from left to right, where x^0 is at
+/// index 0.
+pub(crate) fn random_polynomial(
+    secret: Scalar,
+    shares_required: u8,
+    rng: &mut dyn RngCore,
+) -> Vec<Scalar> {
+    let mut coeffs = Vec::with_capacity(shares_required as usize);
+    coeffs.push(secret);
+    for _ in 1..shares_required {
+        coeffs.push(random_scalar(rng));
+    }
+    coeffs
+}
+
+/// Calculates the y-value of the polynomial for the given x-value.
+pub(crate) fn evaluate(coeffs: &[Scalar], x: u8) -> Scalar {
+    let x = Scalar::from(x);
+    coeffs
+        .iter()
+        .rev()
+        .fold(Scalar::ZERO, |acc, co| acc * x + co)
+}
+
+/// Calculates the Lagrange coefficients needed to find the y-intercept of the polynomial that
+/// runs through points with the given x-values.
+pub(crate) fn lagrange_coefficients(x_values: &[u8]) -> Vec<Scalar> {
+    let x_values: Vec<Scalar> = x_values.iter().map(|x| Scalar::from(*x)).collect();
+    (0..x_values.len())
+        .map(|i| {
+            let mut num = Scalar::ONE;
+            let mut denom = Scalar::ONE;
+            for (j, x_j) in x_values.iter().enumerate() {
+                if i != j {
+                    num *= x_j;
+                    denom *= x_j - x_values[i];
+                }
+            }
+            num * denom.invert()
+        })
+        .collect()
+}
+
+/// Interpolates the y-intercept of each of the **num_chunks** polynomials, where **ys** holds
+/// the y-values of the share with the x-value at the same index in **x_values**.
+pub(crate) fn interpolate<'a>(
+    x_values: &[u8],
+    ys: impl Iterator<Item = &'a [Scalar]>,
+    num_chunks: usize,
+) -> Vec<Scalar> {
+    let lagrange = lagrange_coefficients(x_values);
+    let mut chunks = vec![Scalar::ZERO; num_chunks];
+    for (share_ys, l) in ys.zip(lagrange.iter()) {
+        for (chunk, y) in chunks.iter_mut().zip(share_ys) {
+            *chunk += y * l;
+        }
+    }
+    chunks
+}
+
+/// Verifies each share against the commitments and then reconstructs the secret from them.
+///
+/// **point** returns the x-value and y-values of a share, and **verify** checks a share against
+/// the commitments.
+pub(crate) fn reconstruct_verified<T, S>(
+    shares: &[T],
+    commitments: &Commitments<S>,
+    point: impl Fn(&T) -> (u8, &[Scalar]),
+    verify: impl Fn(&T, &Commitments<S>) -> bool,
+) -> Result<Vec<u8>, Error> {
+    let x_values: Vec<u8> = shares.iter().map(|share| point(share).0).collect();
+    check_x_values(&x_values, commitments.shares_required)?;
+
+    for (share, x) in shares.iter().zip(&x_values) {
+        let ys = point(share).1;
+        if ys.len() != commitments.chunks.len() {
+            return Err(Error::MismatchedLength {
+                expected: commitments.chunks.len(),
+                found: ys.len(),
+            });
+        }
+        if !verify(share, commitments) {
+            return Err(Error::InvalidShare(*x));
+        }
+    }
+
+    // Every share is on the polynomial, so only the minimum number of them is needed
+    let shares = &shares[..commitments.shares_required as usize];
+    let chunks = interpolate(
+        &x_values[..shares.len()],
+        shares.iter().map(|share| point(share).1),
+        commitments.chunks.len(),
+    );
+
+    Ok(scalars_to_secret(&chunks, commitments.secret_len))
+}
+
+/// Calculates Σ commitments\[k\] * x^k, which is the commitment to the y-value of the committed
+/// polynomial at x.
+pub(crate) fn evaluate_commitments(commitments: &[RistrettoPoint], x: u8) -> RistrettoPoint {
+    let x = Scalar::from(x);
+    let mut powers = Vec::with_capacity(commitments.len());
+    let mut curr = Scalar::ONE;
+    for _ in commitments {
+        powers.push(curr);
+        curr *= x;
+    }
+    RistrettoPoint::vartime_multiscalar_mul(powers, commitments)
+}
+
+/// Checks that the given x-values are enough to reconstruct and that none of them are repeated.
+pub(crate) fn check_x_values(x_values: &[u8], shares_required: u8) -> Result<(), Error> {
+    if x_values.len() < shares_required as usize {
+        return Err(Error::NotEnoughShares {
+            given: x_values.len(),
+            required: shares_required,
+        });
+    }
+    for (i, x) in x_values.iter().enumerate() {
+        if *x == 0 {
+            return Err(Error::InvalidShare(*x));
+        }
+        if x_values[..i].contains(x) {
+            return Err(Error::DuplicateShare(*x));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn encode_scalars(out: &mut Vec<u8>, scalars: &[Scalar]) {
+    for scalar in scalars {
+        out.extend_from_slice(scalar.as_bytes());
+    }
+}
+
+pub(crate) fn decode_scalars(bytes: &[u8]) -> Result<Vec<Scalar>, Error> {
+    if !bytes.len().is_multiple_of(ELEMENT_SIZE) {
+        return Err(Error::InvalidEncoding);
+    }
+    bytes
+        .chunks(ELEMENT_SIZE)
+        .map(|chunk| {
+            let mut buf = [0u8; ELEMENT_SIZE];
+            buf.copy_from_slice(chunk);
+            Option::from(Scalar::from_canonical_bytes(buf)).ok_or(Error::InvalidEncoding)
+        })
+        .collect()
+}
+
+pub(crate) fn encode_points(out: &mut Vec<u8>, points: &[RistrettoPoint]) {
+    for point in points {
+        out.extend_from_slice(point.compress().as_bytes());
+    }
+}
+
+pub(crate) fn decode_points(bytes: &[u8]) -> Result<Vec<RistrettoPoint>, Error> {
+    if !bytes.len().is_multiple_of(ELEMENT_SIZE) {
+        return Err(Error::InvalidEncoding);
+    }
+    bytes
+        .chunks(ELEMENT_SIZE)
+        .map(|chunk| {
+            CompressedRistretto::from_slice(chunk)
+                .ok()
+                .and_then(|compressed| compressed.decompress())
+                .ok_or(Error::InvalidEncoding)
+        })
+        .collect()
+}
+
+/// Local Error enum, used to report errors that occur during verifiable sharing.
+#[derive(Debug)]
+pub enum Error {
+    /// The share with this x-value does not match the published commitments
+    InvalidShare(u8),
+    /// More than one share with this x-value was given
+    DuplicateShare(u8),
+    NotEnoughShares { given: usize, required: u8 },
+    /// The share was created for a secret with a different number of chunks than the commitments
+    MismatchedLength { expected: usize, found: usize },
+    /// Bytes could not be decoded into a share or commitments
+    InvalidEncoding,
+    OtherSharingError(crate::basic_sharing::Error),
+}
+
+impl From<crate::basic_sharing::Error> for Error {
+    fn from(source: crate::basic_sharing::Error) -> Self {
+        Error::OtherSharingError(source)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::InvalidShare(x) => write!(
+                f,
+                "Share with x-value {} is not consistent with the commitments",
+                x
+            ),
+            Error::DuplicateShare(x) => {
+                write!(f, "Share with x-value {} was given more than once", x)
+            }
+            Error::NotEnoughShares { given, required } => write!(
+                f,
+                "Not enough shares to recreate secret: Given: {}; Required: {}",
+                given, required
+            ),
+            Error::MismatchedLength { expected, found } => write!(
+                f,
+                "Share has {} chunks but the commitments are for {} chunks",
+                found, expected
+            ),
+            Error::InvalidEncoding => write!(f, "Bytes are not a valid encoding"),
+            Error::OtherSharingError(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,198 @@
+//! Pedersen verifiable secret sharing.
+//!
+//! Every polynomial that shares a chunk of the secret is paired with a random blinding
+//! polynomial, and the dealer publishes `g^a * h^b` for each pair of coefficients `a`, `b`.
+//! Unlike [feldman], the commitments reveal nothing about the secret even to an
+//! attacker with unlimited computing power, so this is safe to use with secrets that can be
+//! guessed, such as those created with
+//! [with_derived_share](crate::derived_share::with_derived_share).
+//!
+//! Each share carries a blinding value alongside every y-value, which is needed to verify it but
+//! not to reconstruct the secret.
+use super::*;
+use crate::basic_sharing::check_share_counts;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use lazy_static::lazy_static;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+lazy_static! {
+    // The second generator, derived by hashing so that no one knows its discrete log relative to
+    // the basepoint
+    static ref H: RistrettoPoint =
+        RistrettoPoint::hash_from_bytes::<sha3::Sha3_512>(b"sss-rs pedersen generator h");
+}
+
+/// Marks [Commitments] as created by Pedersen sharing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pedersen {}
+
+/// The commitments `g^a * h^b` to the coefficients used to create a set of Pedersen shares.
+pub type Commitments = super::Commitments<Pedersen>;
+
+/// A share of a secret, holding one y-value and one blinding value per [CHUNK_SIZE] chunk of the
+/// secret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub x: u8,
+    pub ys: Vec<Scalar>,
+    pub blindings: Vec<Scalar>,
+}
+
+impl Share {
+    /// Encodes the share as
+    /// (1-byte X-value),(32-byte y-value for each chunk),(32-byte blinding value for each chunk)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + 2 * self.ys.len() * ELEMENT_SIZE);
+        bytes.push(self.x);
+        encode_scalars(&mut bytes, &self.ys);
+        encode_scalars(&mut bytes, &self.blindings);
+        bytes
+    }
+
+    /// Decodes a share encoded with [Share::to_bytes]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (x, rest) = bytes.split_first().ok_or(Error::InvalidEncoding)?;
+        let mut ys = decode_scalars(rest)?;
+        if ys.len() % 2 != 0 {
+            return Err(Error::InvalidEncoding);
+        }
+        let blindings = ys.split_off(ys.len() / 2);
+        Ok(Share {
+            x: *x,
+            ys,
+            blindings,
+        })
+    }
+}
+
+/// Creates shares of the secret along with the hiding commitments needed to verify them.
+///
+/// See [feldman::from_secrets] for documentation on the arguments.
+pub fn from_secrets(
+    secret: &[u8],
+    shares_required: u8,
+    shares_to_create: u8,
+    rand: Option<&mut dyn RngCore>,
+) -> Result<(Vec<Share>, Commitments), Error> {
+    if secret.is_empty() {
+        return Err(crate::basic_sharing::Error::EmptySecretArray.into());
+    }
+    check_share_counts(shares_required, shares_to_create)?;
+
+    let mut from_entropy: Box<dyn RngCore>;
+    let rand = match rand {
+        Some(rng) => rng,
+        None => {
+            from_entropy = Box::new(StdRng::from_entropy());
+            &mut from_entropy
+        }
+    };
+
+    // Each secret polynomial is paired with a blinding polynomial with a random y-intercept
+    let polys: Vec<(Vec<Scalar>, Vec<Scalar>)> = secret_to_scalars(secret, rand)
+        .into_iter()
+        .map(|chunk| {
+            let blinding = random_scalar(rand);
+            (
+                random_polynomial(chunk, shares_required, rand),
+                random_polynomial(blinding, shares_required, rand),
+            )
+        })
+        .collect();
+
+    let shares = (1..=shares_to_create)
+        .map(|x| Share {
+            x,
+            ys: polys.iter().map(|(poly, _)| evaluate(poly, x)).collect(),
+            blindings: polys.iter().map(|(_, blind)| evaluate(blind, x)).collect(),
+        })
+        .collect();
+
+    let commitments = Commitments::new(
+        secret.len(),
+        shares_required,
+        polys
+            .iter()
+            .map(|(poly, blind)| {
+                poly.iter()
+                    .zip(blind.iter())
+                    .map(|(a, b)| RistrettoPoint::mul_base(a) + *H * b)
+                    .collect()
+            })
+            .collect(),
+    );
+
+    Ok((shares, commitments))
+}
+
+/// Checks that the share and its blinding values lie on the polynomials committed to by
+/// **commitments**.
+///
+/// Returns true if the share is valid.
+pub fn verify_share(share: &Share, commitments: &Commitments) -> bool {
+    if share.x == 0
+        || share.ys.len() != commitments.chunks.len()
+        || share.blindings.len() != commitments.chunks.len()
+    {
+        return false;
+    }
+    share
+        .ys
+        .iter()
+        .zip(share.blindings.iter())
+        .zip(commitments.chunks.iter())
+        .all(|((y, blinding), chunk)| {
+            RistrettoPoint::mul_base(y) + *H * blinding == evaluate_commitments(chunk, share.x)
+        })
+}
+
+/// Verifies each share against the commitments and then reconstructs the secret from them.
+///
+/// Returns [Error::InvalidShare] with the x-value of the first share that fails verification.
+pub fn reconstruct_secrets(shares: &[Share], commitments: &Commitments) -> Result<Vec<u8>, Error> {
+    reconstruct_verified(
+        shares,
+        commitments,
+        |share| (share.x, share.ys.as_slice()),
+        verify_share,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn share_verify_reconstruct() {
+        let mut rand = StdRng::seed_from_u64(123u64);
+        let secret: Vec<u8> = (0..70).collect();
+        let (shares, commitments) = from_secrets(&secret, 3, 4, Some(&mut rand)).unwrap();
+
+        for share in &shares {
+            assert!(verify_share(share, &commitments));
+        }
+
+        let recon = reconstruct_secrets(&shares[1..], &commitments).unwrap();
+        assert_eq!(secret, recon);
+    }
+
+    #[test]
+    fn tampered_blinding_detected() {
+        let secret = vec![7];
+        let (mut shares, commitments) = from_secrets(&secret, 2, 3, None).unwrap();
+        shares[0].blindings[0] += Scalar::ONE;
+
+        assert!(!verify_share(&shares[0], &commitments));
+        assert!(matches!(
+            reconstruct_secrets(&shares, &commitments),
+            Err(Error::InvalidShare(1))
+        ));
+
+        let share = Share::from_bytes(&shares[2].to_bytes()).unwrap();
+        assert!(verify_share(&share, &commitments));
+    }
+}
@@ -1,11 +1,18 @@
-use crate::basic_sharing::{from_secrets, reconstruct_secrets};
+use crate::basic_sharing::{from_secrets_wide, reconstruct_secrets_wide};
+use crate::batch::{share_segment, Reconstructor};
+use crate::share_header::{
+    self, check_headers, HashAlgorithm, Scheme, ShareHeader, CHECKSUM_LEN, FLAG_CHECKSUM,
+    HEADER_LEN, MAC_KEY_LEN, MAGIC,
+};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use sha3::Digest;
-use std::convert::TryFrom;
 use std::fs::File;
 use std::io::{Cursor, Read, Write, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
-const NUM_FIRST_BYTES_FOR_VERIFY: usize = 32;
+pub(crate) const NUM_FIRST_BYTES_FOR_VERIFY: usize = 32;
 pub const READ_SEGMENT_SIZE: usize = 8_192; // 8 KB, which has shown optimal perforamnce
 
 /// Contains the secret, whether in file or in memory stored in a Vec of bytes.
@@ -25,18 +32,15 @@ pub enum Secret {
     InFile(File)
 }
 
-trait SecretTrait {
+pub(crate) trait SecretTrait {
     fn calculate_hash(&mut self) -> Result<Vec<u8>, Error>;
 
     fn len(&mut self) -> Result<u64, Error>;
-
-    fn verify(&mut self, hash: &[u8]) -> Result<bool, Error>;
 }
 
 
 impl<T: Read + Seek> SecretTrait for T {
     fn calculate_hash(&mut self) -> Result<Vec<u8>, Error> {
-        let mut hasher = sha3::Sha3_512::new();
         let len = self.len()?;
         let hash_input_num_bytes = if len < (NUM_FIRST_BYTES_FOR_VERIFY as u64) {
             len as usize
@@ -47,9 +51,7 @@ impl<T: Read + Seek> SecretTrait for T {
         let mut input_vec = Vec::with_capacity(hash_input_num_bytes);
         self.take(hash_input_num_bytes as u64)
             .read_to_end(&mut input_vec)?;
-        hasher.update(input_vec.as_slice());
-        let hasher_output = hasher.finalize();
-        Ok(hasher_output.to_vec())
+        Ok(hash_first_bytes(&input_vec))
     }
 
 
@@ -59,8 +61,67 @@ impl<T: Read + Seek> SecretTrait for T {
         self.rewind()?;
         Ok(len)
     }
-    fn verify(&mut self, hash: &[u8]) -> Result<bool, Error> {
-        Ok(self.calculate_hash()? == hash.to_vec())
+}
+// Calculates the verification hash from the first NUM_FIRST_BYTES_FOR_VERIFY bytes of the
+// secret, which may be given more bytes than that
+pub(crate) fn hash_first_bytes(secret: &[u8]) -> Vec<u8> {
+    let len = secret.len().min(NUM_FIRST_BYTES_FOR_VERIFY);
+    sha3::Sha3_512::digest(&secret[..len]).to_vec()
+}
+
+// Calculates the verification hash of a secret from its segments as they are read or
+// reconstructed, so the secret doesn't need to be read a second time. A MAC uses the Blake3
+// variant with a keyed hasher.
+pub(crate) enum SecretHasher {
+    None,
+    // Holds the first NUM_FIRST_BYTES_FOR_VERIFY bytes
+    Sha3_512Prefix(Vec<u8>),
+    Sha3_512(Box<sha3::Sha3_512>),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl SecretHasher {
+    // **key** is the MAC key shared in front of the secret, which is empty unless hash_algorithm
+    // is a MAC
+    pub(crate) fn new(hash_algorithm: HashAlgorithm, key: &[u8]) -> Result<Self, Error> {
+        Ok(match hash_algorithm {
+            HashAlgorithm::None => SecretHasher::None,
+            HashAlgorithm::Sha3_512Prefix => {
+                SecretHasher::Sha3_512Prefix(Vec::with_capacity(NUM_FIRST_BYTES_FOR_VERIFY))
+            }
+            HashAlgorithm::Sha3_512 => SecretHasher::Sha3_512(Box::new(sha3::Sha3_512::new())),
+            HashAlgorithm::Blake3 => SecretHasher::Blake3(Box::new(blake3::Hasher::new())),
+            HashAlgorithm::Blake3Mac => {
+                // A key of the wrong length can only be reconstructed from truncated shares
+                let key = <[u8; MAC_KEY_LEN]>::try_from(key)
+                    .map_err(|_| crate::basic_sharing::Error::InvalidShareEncoding)?;
+                SecretHasher::Blake3(Box::new(blake3::Hasher::new_keyed(&key)))
+            }
+        })
+    }
+
+    pub(crate) fn update(&mut self, segment: &[u8]) {
+        match self {
+            SecretHasher::None => (),
+            SecretHasher::Sha3_512Prefix(first_bytes) => {
+                let needed = NUM_FIRST_BYTES_FOR_VERIFY.saturating_sub(first_bytes.len());
+                first_bytes.extend_from_slice(&segment[..needed.min(segment.len())]);
+            }
+            SecretHasher::Sha3_512(hasher) => hasher.update(segment),
+            SecretHasher::Blake3(hasher) => {
+                hasher.update(segment);
+            }
+        }
+    }
+
+    // Returns None if there is no hash
+    pub(crate) fn finalize(self) -> Option<Vec<u8>> {
+        match self {
+            SecretHasher::None => None,
+            SecretHasher::Sha3_512Prefix(first_bytes) => Some(hash_first_bytes(&first_bytes)),
+            SecretHasher::Sha3_512(hasher) => Some(hasher.finalize().to_vec()),
+            SecretHasher::Blake3(hasher) => Some(hasher.finalize().as_bytes().to_vec()),
+        }
     }
 }
 
@@ -157,7 +218,7 @@ impl Secret {
     /// reconstruction resulting in the first 64 bytes being correct is extremely low.
     ///
     /// If $secret.len() is less than 64 bytes, then only $secret.len() number of bytes is used.
-
+    ///
     /// Calculcates and returns the hash of the first 64 bytes of the share in a string with
     /// hexidecimal digits.
     pub fn get_hash_hex(&mut self) -> Result<String, Error> {
@@ -311,37 +372,172 @@ impl<'a> std::iter::Iterator for SecretIterator<'a> {
 ///
 /// secret will have rewind() called on it
 ///
+/// Each share starts with a [ShareHeader] describing the sharing.
+///
 /// **verify**: If true, a hash is calculated from the secret and placed at the end to be used
 ///             to verify reconstruction of the secret.
 pub fn share_to_writables<'a, T: Read + Seek>(
-    mut secret: T,
+    secret: T,
     dests: &mut Vec<Box<dyn Write + 'a>>,
     shares_required: u8,
     shares_to_create: u8,
     verify: bool,
 ) -> Result<(), Error> {
-    secret.rewind()?;
-    // This just writes each corresponding share_list in share_lists to a dest in dests. This
-    // is written here as a closure since it's used at two different points in this function
-    let share_lists_to_dests =
-        |lists: Vec<Vec<(u8, u8)>>, dests: &mut Vec<Box<dyn Write + 'a>>| -> Result<(), Error> {
-            for (share_list, dest) in lists.into_iter().zip(dests.iter_mut()) {
-                dest.write_all(
-                    share_list
-                        .into_iter()
-                        .map(|(_, y)| y)
-                        .collect::<Vec<u8>>()
-                        .as_slice(),
-                )?;
-            }
-            Ok(())
-        };
+    share_to_writables_with_options(
+        secret,
+        dests,
+        shares_required,
+        shares_to_create,
+        &ShareOptions::from_verify(verify),
+    )
+}
 
-    // Write out the x value to each dest that will be used for each following point
-    for (x_val, dest) in dests.iter_mut().enumerate() {
-        dest.write_all(&[(x_val + 1) as u8])?;
+/// Options for the shares created by the `_with_options` sharing functions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareOptions {
+    /// The hash shared along with the secret to verify its reconstruction
+    pub hash_algorithm: HashAlgorithm,
+    /// If true, a BLAKE3 checksum of each share is appended to it, so that corrupted shares can
+    /// be found and skipped before reconstruction
+    pub checksum: bool,
+}
+
+impl Default for ShareOptions {
+    fn default() -> Self {
+        Self {
+            hash_algorithm: HashAlgorithm::Blake3Mac,
+            checksum: true,
+        }
+    }
+}
+
+impl ShareOptions {
+    // The options used by the functions that only take verify
+    pub(crate) fn from_verify(verify: bool) -> Self {
+        Self {
+            hash_algorithm: hash_algorithm(verify),
+            checksum: false,
+        }
     }
+}
 
+/// Same as [share_to_writables], with the hash and checksum set by **options**
+pub fn share_to_writables_with_options<'a, T: Read + Seek>(
+    mut secret: T,
+    dests: &mut Vec<Box<dyn Write + 'a>>,
+    shares_required: u8,
+    shares_to_create: u8,
+    options: &ShareOptions,
+) -> Result<(), Error> {
+    let mut rand = StdRng::from_entropy();
+    let header = ShareHeader::new(
+        Scheme::Gf256,
+        shares_required as u16,
+        secret.len()?,
+        options.hash_algorithm,
+        &mut rand,
+    );
+    share_with_header(dests, header, options.checksum, |dests| {
+        share_to_writables_with_rng(
+            secret,
+            dests,
+            shares_required,
+            shares_to_create,
+            options.hash_algorithm,
+            READ_SEGMENT_SIZE,
+            &mut rand,
+        )
+    })
+}
+
+// Writes the header to each dest, then calls **share** to write the rest of the shares. If
+// **checksum** is true, a checksum of everything written to a dest is appended to it.
+pub(crate) fn share_with_header<'a>(
+    dests: &mut Vec<Box<dyn Write + 'a>>,
+    mut header: ShareHeader,
+    checksum: bool,
+    share: impl FnOnce(&mut Vec<Box<dyn Write + '_>>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if !checksum {
+        write_headers(dests, &header)?;
+        return share(dests);
+    }
+
+    header.flags |= FLAG_CHECKSUM;
+    // Everything written to a dest is also hashed, so the checksum can be appended at the end
+    let mut hashers = vec![blake3::Hasher::new(); dests.len()];
+    {
+        let mut checksum_dests: Vec<Box<dyn Write + '_>> = dests
+            .iter_mut()
+            .zip(hashers.iter_mut())
+            .map(|(dest, hasher)| Box::new(ChecksumWriter { dest, hasher }) as Box<dyn Write>)
+            .collect();
+        write_headers(&mut checksum_dests, &header)?;
+        share(&mut checksum_dests)?;
+    }
+    for (dest, hasher) in dests.iter_mut().zip(&hashers) {
+        dest.write_all(hasher.finalize().as_bytes())?;
+        dest.flush().ok();
+    }
+    Ok(())
+}
+
+// Writes to dest, and hashes everything that was written
+struct ChecksumWriter<'h, W> {
+    dest: W,
+    hasher: &'h mut blake3::Hasher,
+}
+
+impl<W: Write> Write for ChecksumWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        let written = self.dest.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.dest.flush()
+    }
+}
+
+// Writes the header to each dest, with the index of the share that dest receives
+pub(crate) fn write_headers<'a>(
+    dests: &mut [Box<dyn Write + 'a>],
+    header: &ShareHeader,
+) -> Result<(), Error> {
+    for (i, dest) in dests.iter_mut().enumerate() {
+        dest.write_all(&header.for_index(i as u16 + 1).to_bytes())?;
+    }
+    Ok(())
+}
+
+// Draws a random MAC key to share in front of the secret, which is empty unless
+// **hash_algorithm** is a MAC
+fn mac_key(hash_algorithm: HashAlgorithm, rand: &mut dyn RngCore) -> Vec<u8> {
+    let mut key = vec![0u8; hash_algorithm.key_len()];
+    rand.fill_bytes(&mut key);
+    key
+}
+
+pub(crate) fn hash_algorithm(verify: bool) -> HashAlgorithm {
+    if verify {
+        HashAlgorithm::Sha3_512Prefix
+    } else {
+        HashAlgorithm::None
+    }
+}
+
+// Each segment is shared with its own rng seeded from **rand**, so that the segments can be
+// shared in any order (or in parallel) and still produce the same shares.
+pub(crate) fn share_to_writables_with_rng<'a, T: Read + Seek>(
+    mut secret: T,
+    dests: &mut Vec<Box<dyn Write + 'a>>,
+    shares_required: u8,
+    shares_to_create: u8,
+    hash_algorithm: HashAlgorithm,
+    segment_size: usize,
+    rand: &mut dyn RngCore,
+) -> Result<(), Error> {
+    secret.rewind()?;
     if dests.len() < (shares_to_create as usize) {
         // Not enough dests to share shares to
         return Err(Error::NotEnoughWriteableDestinations(
@@ -349,37 +545,26 @@ pub fn share_to_writables<'a, T: Read + Seek>(
             shares_to_create,
         ));
     }
-    let mut finished = false;
-    while !finished {
+
+    // Write out the x value to each dest that will be used for each following point, along with
+    // the MAC key if there is one
+    let (mut sharer, starts) =
+        SegmentSharer::new(shares_required, shares_to_create, hash_algorithm, rand)?;
+    write_shares(dests, &starts)?;
+    loop {
         // Return error if seret_segment is an error, or unwrap it if its ok. This can happen
         // if the secret is a file and a reading error occured during iteration
-        let mut secret_segment = Vec::with_capacity(READ_SEGMENT_SIZE);
-        (&mut secret).take(READ_SEGMENT_SIZE as u64).read_to_end(&mut secret_segment)?;
-
-        if !secret_segment.is_empty() {
-            let share_lists = from_secrets(
-                secret_segment.as_slice(),
-                shares_required,
-                shares_to_create,
-                None,
-                None,
-            )?;
-            share_lists_to_dests(share_lists, dests)?;
-        }
-        else {
-            finished = true;
+        let mut secret_segment = Vec::with_capacity(segment_size);
+        (&mut secret).take(segment_size as u64).read_to_end(&mut secret_segment)?;
+        if secret_segment.is_empty() {
+            break;
         }
-
+        write_shares(dests, &sharer.next_segment(secret_segment, rand).share()?)?;
     }
 
-    if verify {
-        // Now that all of the shares have been written to, calculate the hash and share the hash
-        // to the dests
-        let hash: Vec<u8> = secret.calculate_hash()?.to_vec();
-        let share_lists = from_secrets(&hash, shares_required, shares_to_create, None, None)?;
-
-        // The shares for the hash have been created, write them all to dests
-        share_lists_to_dests(share_lists, dests)?;
+    if let Some(hash) = sharer.finish(rand) {
+        // Now that all of the shares have been written to, share the hash to the dests
+        write_shares(dests, &hash.share()?)?;
     }
 
     // Flush writes to all dests to ensure all bytes are written
@@ -389,6 +574,99 @@ pub fn share_to_writables<'a, T: Read + Seek>(
     Ok(())
 }
 
+// Writes each share's bytes to its dest
+pub(crate) fn write_shares<'a>(
+    dests: &mut [Box<dyn Write + 'a>],
+    shares: &[Vec<u8>],
+) -> Result<(), Error> {
+    for (share, dest) in shares.iter().zip(dests.iter_mut()) {
+        dest.write_all(share)?;
+    }
+    Ok(())
+}
+
+// Shares a secret one segment at a time without doing any I/O, so that the sequential,
+// parallel and async functions create the same shares from the same rng. The rng for the whole
+// secret is passed to each call rather than kept, so callers can hold on to it in between.
+pub(crate) struct SegmentSharer {
+    shares_required: u8,
+    shares_to_create: u8,
+    hasher: SecretHasher,
+}
+
+impl SegmentSharer {
+    // Returns the sharer along with the start of each share, which is its x-value followed by
+    // its share of the MAC key if **hash_algorithm** is a MAC
+    pub(crate) fn new(
+        shares_required: u8,
+        shares_to_create: u8,
+        hash_algorithm: HashAlgorithm,
+        rand: &mut dyn RngCore,
+    ) -> Result<(Self, Vec<Vec<u8>>), Error> {
+        let key = mac_key(hash_algorithm, rand);
+        let sharer = Self {
+            shares_required,
+            shares_to_create,
+            hasher: SecretHasher::new(hash_algorithm, &key)?,
+        };
+        let mut starts: Vec<Vec<u8>> = (1..=shares_to_create).map(|x_val| vec![x_val]).collect();
+        if !key.is_empty() {
+            for (start, key_share) in starts.iter_mut().zip(sharer.task(key, rand).share()?) {
+                start.extend_from_slice(&key_share);
+            }
+        }
+        Ok((sharer, starts))
+    }
+
+    // Adds the next segment of the secret to the hash and seeds its rng. The returned task can
+    // then be shared on any thread, as long as the shares are written out in order.
+    pub(crate) fn next_segment(&mut self, segment: Vec<u8>, rand: &mut dyn RngCore) -> SegmentTask {
+        self.hasher.update(&segment);
+        self.task(segment, rand)
+    }
+
+    // Returns the task that shares the hash, which goes at the end of the shares, or None if
+    // there is no hash
+    pub(crate) fn finish(mut self, rand: &mut dyn RngCore) -> Option<SegmentTask> {
+        let hash = std::mem::replace(&mut self.hasher, SecretHasher::None).finalize()?;
+        Some(self.task(hash, rand))
+    }
+
+    fn task(&self, segment: Vec<u8>, rand: &mut dyn RngCore) -> SegmentTask {
+        SegmentTask {
+            segment,
+            shares_required: self.shares_required,
+            shares_to_create: self.shares_to_create,
+            rand: segment_rng(rand),
+        }
+    }
+}
+
+// A segment of the secret along with the rng it is shared with
+pub(crate) struct SegmentTask {
+    segment: Vec<u8>,
+    shares_required: u8,
+    shares_to_create: u8,
+    rand: ChaCha20Rng,
+}
+
+impl SegmentTask {
+    // Shares the segment, returning the y-values of each share
+    pub(crate) fn share(mut self) -> Result<Vec<Vec<u8>>, Error> {
+        let mut shares =
+            vec![Vec::with_capacity(self.segment.len()); self.shares_to_create as usize];
+        share_segment(&self.segment, self.shares_required, &mut shares, &mut self.rand)?;
+        Ok(shares)
+    }
+}
+
+// Seeds the rng used to share a single segment from the rng for the whole secret
+fn segment_rng(rand: &mut dyn RngCore) -> ChaCha20Rng {
+    let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+    rand.fill_bytes(&mut seed);
+    ChaCha20Rng::from_seed(seed)
+}
+
 
 pub fn share(
     secret: &[u8],
@@ -399,6 +677,16 @@ pub fn share(
     share_from_buf(Cursor::new(secret), shares_required, shares_to_create, verify)
 }
 
+/// Same as [share], with the hash and checksum set by **options**
+pub fn share_with_options(
+    secret: &[u8],
+    shares_required: u8,
+    shares_to_create: u8,
+    options: &ShareOptions,
+) -> Result<Vec<Vec<u8>>, Error> {
+    share_from_buf_with_options(Cursor::new(secret), shares_required, shares_to_create, options)
+}
+
 // TODO: Optimize me, there is a full copy that's done on all the shares, there must be a way to
 // avoid this.
 /// Creates the shares and places them into a Vec of Vecs. This wraps around
@@ -406,14 +694,30 @@ pub fn share(
 ///
 /// secret will have rewind() called on it
 pub fn share_from_buf<T: Read + Seek>(
-    mut secret: T,
+    secret: T,
     shares_required: u8,
     shares_to_create: u8,
     verify: bool,
+) -> Result<Vec<Vec<u8>>, Error> {
+    share_from_buf_with_options(
+        secret,
+        shares_required,
+        shares_to_create,
+        &ShareOptions::from_verify(verify),
+    )
+}
+
+/// Same as [share_from_buf], with the hash and checksum set by **options**
+pub fn share_from_buf_with_options<T: Read + Seek>(
+    mut secret: T,
+    shares_required: u8,
+    shares_to_create: u8,
+    options: &ShareOptions,
 ) -> Result<Vec<Vec<u8>>, Error> {
     secret.rewind()?;
-    let share_len = secret.len()? + 1 + 64;
-    if share_len > std::usize::MAX as u64 {
+    let extra_len = HEADER_LEN + 1 + options.hash_algorithm.key_len() + 64 + CHECKSUM_LEN;
+    let share_len = secret.len()? + extra_len as u64;
+    if share_len > usize::MAX as u64 {
         return Err(Error::SecretTooLarge(secret.len()?));
     }
     let share_len = share_len as usize;
@@ -425,12 +729,12 @@ pub fn share_from_buf<T: Read + Seek>(
         dests.push(Box::new(share_vec_clone) as Box<dyn Write>);
     }
 
-    share_to_writables(
+    share_to_writables_with_options(
         secret,
         &mut dests,
         shares_required,
         shares_to_create,
-        verify,
+        options,
     )?;
     unsafe {
         Ok(dests
@@ -457,6 +761,25 @@ pub fn share_to_files<T: AsRef<Path>, U: Read + Seek>(
     shares_required: u8,
     shares_to_create: u8,
     verify: bool,
+) -> Result<(), Error> {
+    share_to_files_with_options(
+        secret,
+        dir,
+        stem,
+        shares_required,
+        shares_to_create,
+        &ShareOptions::from_verify(verify),
+    )
+}
+
+/// Same as [share_to_files], with the hash and checksum set by **options**
+pub fn share_to_files_with_options<T: AsRef<Path>, U: Read + Seek>(
+    secret: U,
+    dir: T,
+    stem: &str,
+    shares_required: u8,
+    shares_to_create: u8,
+    options: &ShareOptions,
 ) -> Result<(), Error> {
     let file_paths = generate_share_file_paths(dir, stem, shares_to_create);
 
@@ -467,12 +790,12 @@ pub fn share_to_files<T: AsRef<Path>, U: Read + Seek>(
         dests.push(Box::new(f) as Box<dyn Write>);
     }
 
-    share_to_writables(
+    share_to_writables_with_options(
         secret,
         &mut dests,
         shares_required,
         shares_to_create,
-        verify,
+        options,
     )
 }
 
@@ -482,11 +805,18 @@ pub fn share_to_files<T: AsRef<Path>, U: Read + Seek>(
 ///
 /// **verify**: If true, a hash is assumed to exist at the end of the secret and will be used
 ///             to verify secret reconstruction. NOTE: This will fail if the secret was not
-///             shared with verify set to true.
+///             shared with verify set to true. If the shares have headers, the hash
+///             algorithm is taken from them instead, but they must still have a hash if this
+///             is true.
+///
+/// Shares with a checksum that doesn't match are skipped, and [Error::CorruptShare] is returned
+/// for the first of them if too few shares are left to reconstruct the secret. If the other
+/// shares have checksums, shares without one are skipped too, since their header is damaged.
 pub fn reconstruct_to_buf<T: Read + Write + Seek>(secret: T, srcs: &[Vec<u8>], verify: bool) -> Result<(), Error> {
-    let src_len = srcs[0].len() as u64;
+    let srcs = drop_corrupt_shares(srcs)?;
+    let src_len = srcs.first().ok_or(Error::InvalidNumberOfShares(0))?.len() as u64;
     let mut srcs = srcs
-        .into_iter()
+        .iter()
         .map(|share| Box::new(Cursor::new(share)) as Box<dyn Read>)
         .collect();
     reconstruct_from_srcs(secret, &mut srcs, src_len, verify)
@@ -494,24 +824,80 @@ pub fn reconstruct_to_buf<T: Read + Write + Seek>(secret: T, srcs: &[Vec<u8>], v
 
 
 /// Reconstructs a secret to a vec
+///
+/// Corrupt shares are skipped in the same way as [reconstruct_to_buf].
 pub fn reconstruct(srcs: &[Vec<u8>], verify: bool) -> Result<Vec<u8>, Error> {
-    let len = srcs.get(0).ok_or(Error::InvalidNumberOfShares(0))?.len();
+    let len = srcs.first().ok_or(Error::InvalidNumberOfShares(0))?.len();
     let mut buf = Cursor::new(Vec::with_capacity(len));
-    let src_len = srcs[0].len() as u64;
-    let mut srcs = srcs
-        .into_iter()
-        .map(|share| Box::new(Cursor::new(share)) as Box<dyn Read>)
-        .collect();
-    reconstruct_from_srcs(&mut buf, &mut srcs, src_len, verify)?;
+    reconstruct_to_buf(&mut buf, srcs, verify)?;
     Ok(buf.into_inner())
 }
 
+// Removes the shares that are corrupt according to [share_header::corrupt_shares]. If too few
+// shares are left to reconstruct the secret, the error for the first corrupt share is returned
+// instead.
+fn drop_corrupt_shares(srcs: &[Vec<u8>]) -> Result<Vec<&Vec<u8>>, Error> {
+    let corrupt = share_header::corrupt_shares(srcs);
+    let good: Vec<&Vec<u8>> = srcs
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !corrupt.contains(index))
+        .map(|(_, share)| share)
+        .collect();
+    if let Some(&index) = corrupt.first() {
+        let threshold = match good.first() {
+            Some(share) => ShareHeader::from_bytes(share)?.threshold as usize,
+            None => 1,
+        };
+        if good.len() < threshold {
+            return Err(Error::CorruptShare { index, path: None });
+        }
+    }
+    Ok(good)
+}
+
+// Returns whether the checksum at the end of the src matches the rest of it, or None if the src
+// doesn't have a header with FLAG_CHECKSUM set. The src is rewound afterwards.
+fn checksum_matches_src<R: Read + Seek>(src: &mut R) -> Result<Option<bool>, Error> {
+    let len = src.len()?;
+    let mut header_bytes = Vec::with_capacity(HEADER_LEN);
+    src.take(HEADER_LEN as u64).read_to_end(&mut header_bytes)?;
+    let matches = match ShareHeader::from_bytes(&header_bytes) {
+        Ok(header) if header.has_checksum() => {
+            if len < (HEADER_LEN + CHECKSUM_LEN) as u64 {
+                Some(false)
+            } else {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(&header_bytes);
+                std::io::copy(
+                    &mut src.take(len - (HEADER_LEN + CHECKSUM_LEN) as u64),
+                    &mut hasher,
+                )?;
+                let mut checksum = [0u8; CHECKSUM_LEN];
+                src.read_exact(&mut checksum)?;
+                Some(hasher.finalize().as_bytes() == &checksum)
+            }
+        }
+        _ => None,
+    };
+    src.rewind()?;
+    Ok(matches)
+}
 
 /// Reconstructs a secret from a given list of srcs. The srcs should all read the same number
 /// of bytes.
 /// Will rewind() secrets
 ///
 /// **src_len** MUST be an accurate length of the shares
+///
+/// If the shares start with a [ShareHeader], it is checked against the other shares and
+/// **src_len**, and whether there is a hash to verify is taken from it instead of **verify**.
+/// [Error::NoHashToVerify] is returned if **verify** is true and the header says there is no
+/// hash.
+/// Headerless shares from older versions are reconstructed as before.
+///
+/// Checksums are not checked since the srcs can only be read once, see [reconstruct_to_buf] and
+/// [reconstruct_from_files_checked] for functions that skip corrupt shares.
 pub fn reconstruct_from_srcs<'a, T: Read + Write + Seek>(
     mut secret: T,
     srcs: &mut Vec<Box<dyn Read + 'a>>,
@@ -519,86 +905,308 @@ pub fn reconstruct_from_srcs<'a, T: Read + Write + Seek>(
     verify: bool,
 ) -> Result<(), Error> {
     secret.rewind()?;
-    // This is to avoid multiple reference issues.
-    let to_points = |vec: Vec<u8>, segment_num: u8| -> Vec<(u8, u8)> {
-        vec.into_iter().map(|val| (segment_num, val)).collect()
-    };
-    let get_shares = |num_bytes: usize,
-                      srcs: &mut Vec<Box<dyn Read + 'a>>,
-                      x_vals: &Vec<u8>|
-     -> Result<Vec<Vec<(u8, u8)>>, Error> {
-        let mut segments: Vec<Vec<(u8, u8)>> = Vec::with_capacity(srcs.len());
-
-        // Read in one segment size from each share
-        for (src, x_val) in srcs.iter_mut().zip(x_vals) {
-            let mut buf: Vec<u8> = Vec::with_capacity(num_bytes as usize);
-            src.take(num_bytes as u64).read_to_end(&mut buf)?;
-            segments.push(to_points(buf, *x_val));
-        }
-        Ok(segments)
-    };
+    // First, get the header if there is one and the x value for each share
+    let (x_vals, src_len, hash_algorithm) = read_share_starts(srcs, src_len, verify)?;
+    let key_len = hash_algorithm.key_len();
+    let hash_len = hash_algorithm.digest_len();
+    let mut remaining = src_len
+        .checked_sub((key_len + hash_len) as u64)
+        .ok_or(Error::ReconstructionNotEqual)?;
+    let mut reconstructor =
+        SegmentReconstructor::new(&x_vals, hash_algorithm, &read_shares(srcs, key_len)?)?;
 
-    // First, get the first byte from each share, which is the x value for those shares
-    let mut buf = Vec::with_capacity(1);
-    let mut x_vals = Vec::with_capacity(srcs.len());
+    // Now read in segments and compute the secrets and write the secrets to the destination
+    // Skip the hash at the end, which isn't included in the output secret, it's just used to
+    // verify that the secret was reconstructed properly
+    while remaining > 0 {
+        let segment_size = remaining.min(READ_SEGMENT_SIZE as u64);
+        let segments = read_shares(srcs, segment_size as usize)?;
+        secret.write_all(&reconstructor.next_segment(&segments)?)?;
+        remaining -= segment_size;
+    }
+    secret.flush().ok();
+    reconstructor.finish(&read_shares(srcs, hash_len)?)
+}
+
+// Reads **num_bytes** from each src, or fewer if it ends before then
+pub(crate) fn read_shares<'a>(
+    srcs: &mut [Box<dyn Read + 'a>],
+    num_bytes: usize,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let mut segments: Vec<Vec<u8>> = Vec::with_capacity(srcs.len());
     for src in srcs.iter_mut() {
-        buf.clear();
-        src.take(1)
-            .read_to_end(&mut buf)?;
-        x_vals.push(buf[0]);
+        let mut buf: Vec<u8> = Vec::with_capacity(num_bytes);
+        src.take(num_bytes as u64).read_to_end(&mut buf)?;
+        segments.push(buf);
     }
+    Ok(segments)
+}
 
-    let src_len = if verify {
-        u64::try_from((src_len as i64) - 64 - 1).unwrap()
-    } else {
-        u64::try_from((src_len as i64) - 1).unwrap()
-    };
+// Reconstructs a secret one segment at a time without doing any I/O, and checks it against the
+// hash at the end of the shares. This is shared by the sequential, parallel and async
+// functions.
+pub(crate) struct SegmentReconstructor {
+    // The x-values are the same for the whole share, so the Lagrange weights only need to be
+    // calculated once
+    reconstructor: Reconstructor,
+    hasher: SecretHasher,
+}
 
-    let segments_to_read = if src_len % (READ_SEGMENT_SIZE as u64) != 0 {
-        (src_len / (READ_SEGMENT_SIZE as u64)) + 1
-    } else {
-        src_len / (READ_SEGMENT_SIZE as u64)
-    } as usize;
+impl SegmentReconstructor {
+    // **key_shares** are each share's share of the MAC key that follows its x-value, which are
+    // empty unless **hash_algorithm** is a MAC
+    pub(crate) fn new(
+        x_vals: &[u8],
+        hash_algorithm: HashAlgorithm,
+        key_shares: &[Vec<u8>],
+    ) -> Result<Self, Error> {
+        let reconstructor = Reconstructor::new(x_vals)?;
+        let key = if hash_algorithm.key_len() > 0 {
+            reconstructor.reconstruct(key_shares)?
+        } else {
+            Vec::new()
+        };
+        let hasher = SecretHasher::new(hash_algorithm, &key)?;
+        Ok(Self {
+            reconstructor,
+            hasher,
+        })
+    }
 
-    // 64 is the hash len, which we don't want to include in the output secret, just to verify
-    // that the secret was reconstructed properly. This should never underflow if valid shares
-    // are given since src_len will always be N + 64 where N is the share size
+    // Reconstructs a segment of the secret from the same segment of each share, without adding
+    // it to the hash. Segments can be reconstructed on any thread this way, as long as they are
+    // then passed to [SegmentReconstructor::update] in order.
+    pub(crate) fn reconstruct(&self, segments: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+        Ok(self.reconstructor.reconstruct(segments)?)
+    }
 
-    // Now read in segments and compute the secrets and write the secrets to the destination
-    // Skip the last segment for now since it includes the appended hash
-    let mut curr_len = src_len;
-    if segments_to_read > 0 {
-        while curr_len > 0 {
-            let segment_size = if curr_len < (READ_SEGMENT_SIZE as u64) {
-                curr_len as usize
-            } else {
-                READ_SEGMENT_SIZE
-            };
-            let segments = get_shares(segment_size, srcs, &x_vals)?;
-            // Now segments has a segment from each share src, reconstruct the secret up to that
-            // point and write it to the destination
-            secret.write_all(reconstruct_secrets(segments)?.as_slice())?;
-            curr_len = curr_len.saturating_sub(READ_SEGMENT_SIZE as u64);
+    // Adds the next reconstructed segment of the secret to the hash
+    pub(crate) fn update(&mut self, secret_segment: &[u8]) {
+        self.hasher.update(secret_segment);
+    }
+
+    // Reconstructs the next segment of the secret and adds it to the hash
+    pub(crate) fn next_segment(&mut self, segments: &[Vec<u8>]) -> Result<Vec<u8>, Error> {
+        let secret_segment = self.reconstruct(segments)?;
+        self.update(&secret_segment);
+        Ok(secret_segment)
+    }
+
+    // Checks the hash of the reconstructed secret against the one reconstructed from
+    // **hash_shares**, the end of each share. Does nothing if there is no hash.
+    pub(crate) fn finish(self, hash_shares: &[Vec<u8>]) -> Result<(), Error> {
+        if let Some(calc_hash) = self.hasher.finalize() {
+            let recon_hash = self.reconstructor.reconstruct(hash_shares)?;
+            if recon_hash != calc_hash {
+                return Err(Error::VerificationFailure(
+                    hex::encode(&recon_hash),
+                    hex::encode(&calc_hash),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Reads the header, if the shares have one, and the x-value from the start of each src.
+//
+// Returns the x-values, the number of bytes left in each src after them not including the
+// checksum, and the algorithm of the hash at the end, which is taken from the header if there
+// is one and from **verify** otherwise. Shares with headers must have a hash if **verify** is
+// true.
+pub(crate) fn read_share_starts<'a>(
+    srcs: &mut [Box<dyn Read + 'a>],
+    src_len: u64,
+    verify: bool,
+) -> Result<(Vec<u8>, u64, HashAlgorithm), Error> {
+    let mut first_bytes = Vec::with_capacity(srcs.len());
+    for src in srcs.iter_mut() {
+        let mut byte = [0u8];
+        src.read_exact(&mut byte)?;
+        first_bytes.push(byte[0]);
+    }
+    // x-values are never 0, so a share starting with 0 starts with a header
+    let num_headers = first_bytes
+        .iter()
+        .filter(|byte| **byte == MAGIC[0])
+        .count();
+    if num_headers == 0 {
+        // A headerless share, where the first byte is the x-value
+        return Ok((first_bytes, src_len.saturating_sub(1), hash_algorithm(verify)));
+    }
+    if num_headers != srcs.len() {
+        return Err(share_header::Error::MixedHeaders.into());
+    }
+
+    let mut headers = Vec::with_capacity(srcs.len());
+    let mut x_vals = Vec::with_capacity(srcs.len());
+    for (src, first_byte) in srcs.iter_mut().zip(first_bytes) {
+        let mut bytes = [0u8; HEADER_LEN + 1];
+        bytes[0] = first_byte;
+        src.read_exact(&mut bytes[1..])?;
+        headers.push(ShareHeader::from_bytes(&bytes)?);
+        x_vals.push(bytes[HEADER_LEN]);
+    }
+    let header = check_share_headers(&headers, &x_vals)?;
+    let expected_len = HEADER_LEN as u64 + header.body_len();
+    if src_len != expected_len {
+        return Err(share_header::Error::WrongShareLength {
+            expected: expected_len,
+            found: src_len,
         }
+        .into());
     }
+    // The checksum, if there is one, is left unread at the end
+    let hash_algorithm = header_hash_algorithm(header, verify)?;
+    let body_len =
+        hash_algorithm.key_len() as u64 + header.payload_len + hash_algorithm.digest_len() as u64;
+    Ok((x_vals, body_len, hash_algorithm))
+}
 
+// Returns the algorithm of the hash at the end of shares with this header.
+//
+// The header says whether there is a hash, but it isn't authenticated, so if **verify** is
+// true the shares must have one. Otherwise clearing the hash algorithm in the headers would
+// turn off verification.
+pub(crate) fn header_hash_algorithm(
+    header: &ShareHeader,
+    verify: bool,
+) -> Result<HashAlgorithm, Error> {
+    if verify && header.hash_algorithm == HashAlgorithm::None {
+        return Err(Error::NoHashToVerify);
+    }
+    Ok(header.hash_algorithm)
+}
+
+// Checks the headers of GF(256) shares against the x-values that follow them, returning the
+// header that describes the secret
+pub(crate) fn check_share_headers<'h>(
+    headers: &'h [ShareHeader],
+    x_vals: &[u8],
+) -> Result<&'h ShareHeader, Error> {
+    let header = check_headers(headers, Scheme::Gf256)?;
+    for (header, x_val) in headers.iter().zip(x_vals) {
+        if header.index != *x_val as u16 {
+            return Err(share_header::Error::IndexMismatch {
+                index: header.index,
+                x_value: *x_val as u16,
+            }
+            .into());
+        }
+    }
+    Ok(header)
+}
+
+/// Shares the secret over GF(65536), allowing up to 65535 shares to be created.
+///
+/// The shares are a [ShareHeader] followed by the format described in [from_secrets_wide], where
+/// the secret that is shared is the secret followed by its 64-byte hash if **verify** is true.
+///
+/// **verify**: If true, a hash is calculated from the secret and placed at the end to be used
+///             to verify reconstruction of the secret.
+pub fn share_wide(
+    secret: &[u8],
+    shares_required: u16,
+    shares_to_create: u16,
+    verify: bool,
+) -> Result<Vec<Vec<u8>>, Error> {
+    if secret.is_empty() {
+        return Err(Error::EmptySecret);
+    }
+    let mut wrapped = secret.to_vec();
     if verify {
-        // Now read in the hash
-        let hash_segments = get_shares(64, srcs, &x_vals)?;
-        let recon_hash = reconstruct_secrets(hash_segments)?;
-        // Drop dest since if it is a file, we will be re-opening it to read from it to
-        // calculate the hash. Ensure output is flushed
-        secret.flush().ok();
-        if !secret.verify(recon_hash.as_slice())? {
-            let calc_hash_hex = hex::encode(secret.calculate_hash()?);
-            let orig_hash_hex = hex::encode(&recon_hash);
-            return Err(Error::VerificationFailure(orig_hash_hex, calc_hash_hex));
+        let hash = Cursor::new(secret).calculate_hash()?;
+        wrapped.extend_from_slice(&hash);
+    }
+    let mut rand = StdRng::from_entropy();
+    let header = ShareHeader::new(
+        Scheme::Gf65536,
+        shares_required,
+        secret.len() as u64,
+        hash_algorithm(verify),
+        &mut rand,
+    );
+    let shares = from_secrets_wide(
+        &wrapped,
+        shares_required,
+        shares_to_create,
+        Some(&mut rand),
+    )?;
+    Ok(shares
+        .into_iter()
+        .map(|share| {
+            let index = u16::from_be_bytes([share[0], share[1]]);
+            let mut with_header = header.for_index(index).to_bytes().to_vec();
+            with_header.extend_from_slice(&share);
+            with_header
+        })
+        .collect())
+}
+
+/// Reconstructs a secret from shares created with [share_wide]
+///
+/// **verify**: If true, a hash is assumed to exist at the end of the secret and will be used
+///             to verify secret reconstruction. NOTE: This will fail if the secret was not
+///             shared with verify set to true. If the shares have headers, the hash
+///             algorithm is taken from them instead, but they must still have a hash if this
+///             is true.
+pub fn reconstruct_wide(srcs: &[Vec<u8>], verify: bool) -> Result<Vec<u8>, Error> {
+    let num_headers = srcs.iter().filter(|src| ShareHeader::is_present(src)).count();
+    let (srcs, hash_algorithm) = if num_headers == 0 {
+        (srcs.to_vec(), hash_algorithm(verify))
+    } else if num_headers == srcs.len() {
+        let headers = srcs
+            .iter()
+            .map(|src| ShareHeader::from_bytes(src))
+            .collect::<Result<Vec<_>, _>>()?;
+        let header = check_headers(&headers, Scheme::Gf65536)?;
+        let mut bodies = Vec::with_capacity(srcs.len());
+        for (src, header) in srcs.iter().zip(&headers) {
+            let body = &src[HEADER_LEN..];
+            if body.len() < 2 {
+                return Err(share_header::Error::Truncated.into());
+            }
+            let x_val = u16::from_be_bytes([body[0], body[1]]);
+            if header.index != x_val {
+                return Err(share_header::Error::IndexMismatch {
+                    index: header.index,
+                    x_value: x_val,
+                }
+                .into());
+            }
+            bodies.push(body.to_vec());
+        }
+        (bodies, header_hash_algorithm(header, verify)?)
+    } else {
+        return Err(share_header::Error::MixedHeaders.into());
+    };
+    let mut secret = reconstruct_secrets_wide(srcs)?;
+    let key_len = hash_algorithm.key_len();
+    let hash_len = hash_algorithm.digest_len();
+    if hash_len > 0 {
+        if secret.len() < key_len + hash_len {
+            return Err(Error::ReconstructionNotEqual);
+        }
+        let recon_hash = secret.split_off(secret.len() - hash_len);
+        let key: Vec<u8> = secret.drain(..key_len).collect();
+        let mut hasher = SecretHasher::new(hash_algorithm, &key)?;
+        hasher.update(&secret);
+        let calc_hash = hasher.finalize().unwrap_or_default();
+        if calc_hash != recon_hash {
+            return Err(Error::VerificationFailure(
+                hex::encode(recon_hash),
+                hex::encode(calc_hash),
+            ));
         }
     }
-    Ok(())
+    Ok(secret)
 }
 
 /// Performs the reconstruction of the shares from files with in the given **dir** with the give **stem**
+///
+/// Share files with a checksum that doesn't match are skipped, and the next share file after the
+/// first **shares_required** is used in their place. See [reconstruct_from_files_checked] to find
+/// out which shares were skipped.
 pub fn reconstruct_from_files<T: AsRef<Path>, U: Read + Write + Seek>(
     secret: U,
     dir: T,
@@ -606,34 +1214,81 @@ pub fn reconstruct_from_files<T: AsRef<Path>, U: Read + Write + Seek>(
     shares_required: u8,
     verify: bool,
 ) -> Result<(), Error> {
-    let share_paths = generate_share_file_paths(&dir, stem, shares_required);
-    let share_files: Vec<Result<File, Error>> = share_paths
+    reconstruct_from_files_checked(secret, dir, stem, shares_required, verify).map(|_| ())
+}
+
+/// Same as [reconstruct_from_files], but returns an [Error::CorruptShare] for each share file
+/// that was skipped because its checksum didn't match, or because it has no checksum while the
+/// others do.
+///
+/// If too few share files with matching checksums are found, the error for the first corrupt
+/// share file is returned instead.
+pub fn reconstruct_from_files_checked<T: AsRef<Path>, U: Read + Write + Seek>(
+    secret: U,
+    dir: T,
+    stem: &str,
+    shares_required: u8,
+    verify: bool,
+) -> Result<Vec<Error>, Error> {
+    let mut share_files: Vec<(usize, String, File)> = Vec::with_capacity(shares_required as usize);
+    let mut corrupt = Vec::new();
+    // Once a share file with a matching checksum is found, the ones without a checksum are
+    // corrupt, see share_header::corrupt_shares
+    let mut found_checksum = false;
+    for (index, path) in generate_share_file_paths(&dir, stem, u8::MAX)
         .into_iter()
-        .map(|path| File::open(&path).map_err(|e| Error::FileError(String::from(&path), e)))
+        .enumerate()
+    {
+        if share_files.len() == shares_required as usize {
+            break;
+        }
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            // The share files after the first shares_required are only used to replace corrupt
+            // ones, so they don't have to exist
+            Err(_) if index >= shares_required as usize => break,
+            Err(e) => return Err(Error::FileError(path, e)),
+        };
+        match checksum_matches_src(&mut file)? {
+            Some(true) => {
+                if !found_checksum {
+                    found_checksum = true;
+                    corrupt.extend(share_files.drain(..).map(|(index, path, _)| (index, path)));
+                }
+                share_files.push((index, path, file));
+            }
+            None if !found_checksum => share_files.push((index, path, file)),
+            _ => corrupt.push((index, path)),
+        }
+    }
+    corrupt.sort_by_key(|(index, _)| *index);
+    let mut corrupt: Vec<Error> = corrupt
+        .into_iter()
+        .map(|(index, path)| Error::CorruptShare {
+            index,
+            path: Some(path),
+        })
         .collect();
-
-    // Check that all the share files opened properly
-    let mut unwrapped_share_files: Vec<File> = Vec::with_capacity(share_files.len());
-    for file in share_files {
-        unwrapped_share_files.push(file?);
+    if share_files.len() < shares_required as usize && !corrupt.is_empty() {
+        return Err(corrupt.remove(0));
     }
+    let share_files: Vec<File> = share_files.into_iter().map(|(_, _, file)| file).collect();
+
     // An error here would be extremely rare, so just panic.
-    let len = unwrapped_share_files[0]
+    let len = share_files[0]
         .metadata()
         .expect("An error occured after opening file for read.")
         .len();
 
     // Now map the files to a dyn Read, which needed to wait till we got the len since Read
     // doesn't have a len method.
-    let mut share_files: Vec<Box<dyn Read>> = unwrapped_share_files
+    let mut share_files: Vec<Box<dyn Read>> = share_files
         .into_iter()
         .map(|file| Box::new(file) as Box<dyn Read>)
         .collect();
 
-    let mut secret_path = dir.as_ref().to_path_buf();
-    secret_path.push(stem);
-
-    reconstruct_from_srcs(secret, &mut share_files, len, verify)
+    reconstruct_from_srcs(secret, &mut share_files, len, verify)?;
+    Ok(corrupt)
 }
 
 #[derive(Debug)]
@@ -648,6 +1303,17 @@ pub enum Error {
     FileError(String, std::io::Error),
     IOError(std::io::Error),
     OtherSharingError(crate::basic_sharing::Error),
+    HeaderError(share_header::Error),
+    /// The share at **index**, read from **path** if it was a file, has a checksum that doesn't
+    /// match
+    CorruptShare {
+        index: usize,
+        path: Option<String>,
+    },
+    /// Verification was requested, but the share headers say the shares have no hash
+    NoHashToVerify,
+    #[cfg(feature = "parallel")]
+    ThreadPoolError(rayon::ThreadPoolBuildError),
 }
 
 impl From<crate::basic_sharing::Error> for Error {
@@ -656,6 +1322,12 @@ impl From<crate::basic_sharing::Error> for Error {
     }
 }
 
+impl From<share_header::Error> for Error {
+    fn from(source: share_header::Error) -> Self {
+        Error::HeaderError(source)
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -703,6 +1375,21 @@ Calculated Hash: {}",
             Error::OtherSharingError(source) => {
                 write!(f, "{}", source)
             }
+            Error::HeaderError(source) => {
+                write!(f, "Invalid share header: {}", source)
+            }
+            Error::CorruptShare { index, path } => match path {
+                Some(path) => write!(f, "Share {} at '{}' is corrupt", index, path),
+                None => write!(f, "Share {} is corrupt", index),
+            },
+            Error::NoHashToVerify => write!(
+                f,
+                "Cannot verify the reconstructed secret, the shares were created without a hash"
+            ),
+            #[cfg(feature = "parallel")]
+            Error::ThreadPoolError(source) => {
+                write!(f, "Could not start the worker threads: {}", source)
+            }
         }
     }
 }
@@ -732,7 +1419,7 @@ fn generate_share_file_paths<T: AsRef<Path>>(dir: T, stem: &str, num_files: u8)
 
     for i in 0..num_files {
         path_buf.push(format!("{}.s{}", stem, i));
-        (&mut generated_paths).push(String::from(path_buf.to_str().unwrap()));
+        generated_paths.push(String::from(path_buf.to_str().unwrap()));
         path_buf.pop();
     }
 
@@ -835,6 +1522,217 @@ mod tests {
 
     }
 
+    #[test]
+    fn wide_functions() {
+        let secret = vec![10, 20, 30, 50, 70];
+        let shares = share_wide(&secret, 3, 400, true).unwrap();
+        assert_eq!(HEADER_LEN + 3 + 2 * 35, shares[0].len());
+
+        let recon_secret = reconstruct_wide(&shares[200..203], true).unwrap();
+        assert_eq!(secret, recon_secret);
+
+        let shares = share_wide(&secret, 2, 2, false).unwrap();
+        assert_eq!(secret, reconstruct_wide(&shares, false).unwrap());
+    }
+
+    #[test]
+    fn headers() {
+        let secret: Vec<u8> = (0..100).collect();
+        let shares = share(&secret, 3, 5, true).unwrap();
+        let header = ShareHeader::from_bytes(&shares[4]).unwrap();
+        assert_eq!(
+            (Scheme::Gf256, 3, 5, 100),
+            (header.scheme, header.threshold, header.index, header.payload_len)
+        );
+        assert_eq!(HashAlgorithm::Sha3_512Prefix, header.hash_algorithm);
+
+        // Verify is taken from the header
+        assert_eq!(secret, reconstruct(&shares[1..4], false).unwrap());
+        assert!(matches!(
+            reconstruct(&shares[..2], true),
+            Err(Error::HeaderError(share_header::Error::NotEnoughShares {
+                given: 2,
+                required: 3
+            }))
+        ));
+
+        // Without the header, the shares are in the legacy format
+        let legacy: Vec<Vec<u8>> =
+            shares.iter().map(|share| share[HEADER_LEN..].to_vec()).collect();
+        assert_eq!(secret, reconstruct(&legacy[..3], true).unwrap());
+        assert!(matches!(
+            reconstruct(&[shares[0].clone(), legacy[1].clone(), legacy[2].clone()], true),
+            Err(Error::HeaderError(share_header::Error::MixedHeaders))
+        ));
+
+        // Shares from another sharing of the same secret can't be mixed in
+        let other_shares = share(&secret, 3, 5, true).unwrap();
+        let mixed = vec![shares[0].clone(), other_shares[1].clone(), shares[2].clone()];
+        match reconstruct(&mixed, true) {
+            Err(Error::HeaderError(share_header::Error::MismatchedShareSets {
+                matching,
+                mismatched,
+            })) => assert_eq!((vec![1, 3], vec![2]), (matching, mismatched)),
+            result => panic!("Expected MismatchedShareSets, got {:?}", result),
+        }
+
+        let wide_shares = share_wide(&secret, 2, 3, false).unwrap();
+        assert!(matches!(
+            reconstruct(&wide_shares, false),
+            Err(Error::HeaderError(share_header::Error::WrongScheme(
+                Scheme::Gf65536
+            )))
+        ));
+        let legacy_wide: Vec<Vec<u8>> =
+            wide_shares.iter().map(|share| share[HEADER_LEN..].to_vec()).collect();
+        assert_eq!(secret, reconstruct_wide(&legacy_wide, false).unwrap());
+    }
+
+    #[test]
+    fn full_content_hash() {
+        let secret: Vec<u8> = (0..200).collect();
+        for hash_algorithm in [HashAlgorithm::Sha3_512, HashAlgorithm::Blake3] {
+            let options = ShareOptions {
+                hash_algorithm,
+                checksum: false,
+            };
+            let mut shares = share_with_options(&secret, 2, 3, &options).unwrap();
+            let header = ShareHeader::from_bytes(&shares[0]).unwrap();
+            assert_eq!(hash_algorithm, header.hash_algorithm);
+            assert_eq!(
+                HEADER_LEN + 1 + 200 + hash_algorithm.digest_len(),
+                shares[0].len()
+            );
+            assert_eq!(secret, reconstruct(&shares[1..], false).unwrap());
+
+            // Corruption past the first bytes of the secret is caught
+            shares[1][HEADER_LEN + 1 + 150] ^= 1;
+            assert!(matches!(
+                reconstruct(&shares[1..], false),
+                Err(Error::VerificationFailure(..))
+            ));
+        }
+
+        // Whereas the prefix hash misses it
+        let mut shares = share(&secret, 2, 3, true).unwrap();
+        shares[1][HEADER_LEN + 1 + 150] ^= 1;
+        assert_ne!(secret, reconstruct(&shares[1..], true).unwrap());
+    }
+
+    #[test]
+    fn mac() {
+        let secret: Vec<u8> = (0..200).collect();
+        let options = ShareOptions {
+            hash_algorithm: HashAlgorithm::Blake3Mac,
+            checksum: false,
+        };
+        let mut shares = share_with_options(&secret, 2, 3, &options).unwrap();
+        assert_eq!(HEADER_LEN + 1 + MAC_KEY_LEN + 200 + 32, shares[0].len());
+        assert_eq!(secret, reconstruct(&shares[..2], false).unwrap());
+        let mut recon = Cursor::new(Vec::new());
+        reconstruct_to_buf(&mut recon, &shares[1..], false).unwrap();
+        assert_eq!(secret, recon.into_inner());
+
+        // The shared MAC isn't a plain hash of the secret
+        let bodies: Vec<Vec<u8>> =
+            shares[..2].iter().map(|share| share[HEADER_LEN..].to_vec()).collect();
+        let raw = crate::basic_sharing::reconstruct_secrets_no_points(bodies).unwrap();
+        assert_eq!(secret, raw[MAC_KEY_LEN..MAC_KEY_LEN + 200]);
+        assert_ne!(blake3::hash(&secret).as_bytes(), &raw[MAC_KEY_LEN + 200..]);
+
+        shares[0][HEADER_LEN + 1 + MAC_KEY_LEN + 150] ^= 1;
+        assert!(matches!(
+            reconstruct(&shares[..2], false),
+            Err(Error::VerificationFailure(..))
+        ));
+
+        // A truncated MAC key doesn't fall back to a fixed key
+        assert!(matches!(
+            SecretHasher::new(HashAlgorithm::Blake3Mac, &[7; MAC_KEY_LEN - 1]),
+            Err(Error::OtherSharingError(
+                crate::basic_sharing::Error::InvalidShareEncoding
+            ))
+        ));
+    }
+
+    #[test]
+    fn checksums() {
+        let secret: Vec<u8> = (0..100).collect();
+        let mut shares = share_with_options(&secret, 2, 4, &ShareOptions::default()).unwrap();
+        assert_eq!(HEADER_LEN + 1 + MAC_KEY_LEN + 100 + 32 + CHECKSUM_LEN, shares[0].len());
+        assert!(ShareHeader::from_bytes(&shares[0]).unwrap().has_checksum());
+
+        // A corrupt share is skipped if there are enough others
+        shares[0][HEADER_LEN + 10] ^= 1;
+        assert_eq!(secret, reconstruct(&shares[..3], true).unwrap());
+        assert!(matches!(
+            reconstruct(&shares[..2], true),
+            Err(Error::CorruptShare { index: 0, path: None })
+        ));
+
+        let dir = "./";
+        let stem = "checksums_test";
+        let options = ShareOptions::default();
+        share_to_files_with_options(Cursor::new(&secret), dir, stem, 2, 4, &options).unwrap();
+        let paths = generate_share_file_paths(dir, stem, 4);
+        let corrupt_file = |path: &String| {
+            let mut corrupted = std::fs::read(path).unwrap();
+            corrupted[HEADER_LEN + 10] ^= 1;
+            std::fs::write(path, corrupted).unwrap();
+        };
+        corrupt_file(&paths[1]);
+
+        let mut recon = Cursor::new(Vec::new());
+        let skipped = reconstruct_from_files_checked(&mut recon, dir, stem, 2, true);
+        corrupt_file(&paths[2]);
+        corrupt_file(&paths[3]);
+        let too_few = reconstruct_from_files(Cursor::new(Vec::new()), dir, stem, 2, true);
+        for path in &paths {
+            std::fs::remove_file(path).ok();
+        }
+
+        match skipped.unwrap().as_slice() {
+            [Error::CorruptShare { index: 1, path: Some(path) }] => assert_eq!(&paths[1], path),
+            skipped => panic!("Expected share 1 to be skipped, got {:?}", skipped),
+        }
+        assert_eq!(secret, recon.into_inner());
+        assert!(matches!(too_few, Err(Error::CorruptShare { index: 1, .. })));
+    }
+
+    #[test]
+    fn damaged_headers() {
+        let secret: Vec<u8> = (0..100).collect();
+        let shares = share_with_options(&secret, 2, 4, &ShareOptions::default()).unwrap();
+        // Damage the magic so the header can't be read, or clear the checksum flag
+        for (pos, mask) in [(1, 0xff), (19, FLAG_CHECKSUM)] {
+            let mut shares = shares.clone();
+            shares[0][pos] ^= mask;
+            assert_eq!(secret, reconstruct(&shares[..3], true).unwrap());
+            assert!(matches!(
+                reconstruct(&shares[..2], true),
+                Err(Error::CorruptShare { index: 0, path: None })
+            ));
+
+            let dir = "./";
+            let stem = format!("damaged_headers_test_{}", pos);
+            let paths = generate_share_file_paths(dir, &stem, 4);
+            for (path, share) in paths.iter().zip(&shares) {
+                std::fs::write(path, share).unwrap();
+            }
+            let mut recon = Cursor::new(Vec::new());
+            let skipped = reconstruct_from_files_checked(&mut recon, dir, &stem, 2, true);
+            for path in &paths {
+                std::fs::remove_file(path).ok();
+            }
+
+            match skipped.unwrap().as_slice() {
+                [Error::CorruptShare { index: 0, path: Some(path) }] => assert_eq!(&paths[0], path),
+                skipped => panic!("Expected share 0 to be skipped, got {:?}", skipped),
+            }
+            assert_eq!(secret, recon.into_inner());
+        }
+    }
+
     #[test]
     fn base_functions_no_verify() {
         let secret = vec![10, 20, 30, 50];
@@ -853,5 +1751,28 @@ mod tests {
 
     }
 
+    #[test]
+    fn verify_without_hash() {
+        let secret: Vec<u8> = (0..50).collect();
+        let options = ShareOptions {
+            hash_algorithm: HashAlgorithm::None,
+            checksum: false,
+        };
+        let shares = share_with_options(&secret, 2, 3, &options).unwrap();
+        assert_eq!(secret, reconstruct(&shares[..2], false).unwrap());
+        // Headers saying there is no hash don't turn off verification
+        assert!(matches!(
+            reconstruct(&shares[..2], true),
+            Err(Error::NoHashToVerify)
+        ));
+
+        let shares = share_wide(&secret, 2, 3, false).unwrap();
+        assert_eq!(secret, reconstruct_wide(&shares[1..], false).unwrap());
+        assert!(matches!(
+            reconstruct_wide(&shares[1..], true),
+            Err(Error::NoHashToVerify)
+        ));
+    }
+
 
 }